@@ -0,0 +1,363 @@
+//! A synchronous client, for programs that can't run an async runtime.
+//!
+//! It has the same surface as the async [`crate::YapaySDK`], and shares its request and response
+//! types. Enabled by the `blocking` feature.
+//!
+//! Like the async client, it can't look transactions up yet. Follow their status through the
+//! notifications, see [`crate::YapayWebhook`].
+//!
+//! Requests go straight through `reqwest::blocking`, a [`crate::HttpTransport`] can't be plugged
+//! in, as it is async.
+//!
+//! # Usage
+//!
+//! ```no_run
+//! use yapay_sdk_rust::blocking::YapaySDKBuilder;
+//! use yapay_sdk_rust::YapayEnv;
+//!
+//! # fn main() -> Result<(), yapay_sdk_rust::errors::SDKError> {
+//! let yapay_sdk = YapaySDKBuilder::from_env("YAPAY_ACCOUNT_TOKEN")?;
+//! let simulation = yapay_sdk
+//!     .simulate_payment(1000.0)
+//!     .execute(YapayEnv::PRODUCTION)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Like `reqwest::blocking`, it must not be used from within an async runtime.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use reqwest::blocking::{Client, Response};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::redirect::Policy;
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use zeroize::Zeroizing;
+
+use crate::errors::SDKError;
+use crate::secret::ZeroizingBody;
+use crate::{
+    check_finger_print, checkout_error, checkout_location, credit_card_payload,
+    parse_json_response, restricted_simulate_payload, simulate_payload, AccountToken,
+    AsPaymentMethod, CardTransactionResponse, CheckoutLink, CheckoutPreferences,
+    SimulationResponse, YapayCardData, YapayCustomer, YapayEnv, YapayProduct, YapayTransaction,
+    PAYMENT_ENDPOINT, SIMULATE_ENDPOINT,
+};
+
+/// Builds a blocking [`YapaySDK`].
+#[derive(Copy, Clone, Debug)]
+pub struct YapaySDKBuilder {}
+
+impl YapaySDKBuilder {
+    /// Creates a blocking [`YapaySDK`] ready to request the API.
+    pub fn with_token<T>(account_token: &T) -> YapaySDK
+    where
+        T: ToString,
+    {
+        Self::with_account_token(AccountToken::new(account_token.to_string()))
+    }
+
+    /// Creates a blocking [`YapaySDK`] from an already loaded [`AccountToken`].
+    pub fn with_account_token(account_token: AccountToken) -> YapaySDK {
+        let builder = Client::builder().redirect(Policy::none());
+        #[cfg(feature = "cookies")]
+        let builder = builder.cookie_store(true);
+        let http_client = builder.build().expect("Failed to create client.");
+
+        YapaySDK {
+            http_client,
+            account_token,
+        }
+    }
+
+    /// Creates a blocking [`YapaySDK`] with the token in the environment variable `var`.
+    pub fn from_env(var: &str) -> Result<YapaySDK, SDKError> {
+        AccountToken::from_env(var).map(Self::with_account_token)
+    }
+
+    /// Creates a blocking [`YapaySDK`] with the token in the file at `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<YapaySDK, SDKError> {
+        AccountToken::from_file(path).map(Self::with_account_token)
+    }
+}
+
+#[derive(Debug)]
+pub struct YapaySDK {
+    pub(crate) http_client: Client,
+    pub(crate) account_token: AccountToken,
+}
+
+pub struct SDKJsonRequest<'a, RP> {
+    http_client: &'a Client,
+    method: Method,
+    endpoint: &'a str,
+    /// Wiped on drop, or after the request is sent.
+    payload: Zeroizing<String>,
+    /// Card payment without the anti-fraud finger print, refused in production.
+    missing_finger_print: bool,
+    response_type: PhantomData<RP>,
+}
+
+/// The payload carries the account token, and possibly card data, so only its size is printed.
+impl<'a, RP> fmt::Debug for SDKJsonRequest<'a, RP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SDKJsonRequest")
+            .field("method", &self.method)
+            .field("endpoint", &self.endpoint)
+            .field("payload", &format_args!("{} bytes", self.payload.len()))
+            .finish()
+    }
+}
+
+impl<'a, RP> SDKJsonRequest<'a, RP> {
+    #[must_use]
+    pub fn from_sdk(sdk: &'a YapaySDK, method: Method, endpoint: &'a str, payload: String) -> Self {
+        Self::from_zeroizing(sdk, method, endpoint, Zeroizing::new(payload))
+    }
+
+    fn from_zeroizing(
+        sdk: &'a YapaySDK,
+        method: Method,
+        endpoint: &'a str,
+        payload: Zeroizing<String>,
+    ) -> Self {
+        Self {
+            http_client: &sdk.http_client,
+            method,
+            endpoint,
+            response_type: Default::default(),
+            payload,
+            missing_finger_print: false,
+        }
+    }
+
+    /// Sends the request, blocking until the response is parsed.
+    pub fn execute(mut self, yapay_env: YapayEnv) -> Result<RP, SDKError>
+    where
+        RP: DeserializeOwned,
+    {
+        check_finger_print(self.missing_finger_print, yapay_env)?;
+
+        let body = ZeroizingBody::from(std::mem::take(&mut *self.payload));
+
+        let api_endpoint = format!("{}{}", yapay_env.api_link(), self.endpoint);
+        tracing::trace!("{} {}", self.method, api_endpoint);
+
+        let request = self
+            .http_client
+            .request(self.method, api_endpoint)
+            .body(body)
+            .header(CONTENT_TYPE, "application/json")
+            .build()?;
+
+        let response = self.http_client.execute(request).and_then(Response::text)?;
+        tracing::trace!("response with {} bytes", response.len());
+
+        parse_json_response(&response)
+    }
+}
+
+impl YapaySDK {
+    /// Creates a hosted checkout page, and returns the link your customer should be redirected
+    /// to.
+    ///
+    /// See [`crate::YapaySDK::create_checkout_page`].
+    pub fn create_checkout_page(
+        &self,
+        yapay_env: YapayEnv,
+        checkout_preferences: CheckoutPreferences,
+    ) -> Result<CheckoutLink, SDKError> {
+        self.post_checkout(yapay_env.checkout_link(), checkout_preferences)
+    }
+
+    fn post_checkout(
+        &self,
+        checkout_link: &str,
+        checkout_preferences: CheckoutPreferences,
+    ) -> Result<CheckoutLink, SDKError> {
+        let order_number = checkout_preferences.order_number().to_string();
+        let expires_at = checkout_preferences.expires_at();
+
+        let mut querystring = checkout_preferences.to_form(self.account_token.expose());
+        let request = self
+            .http_client
+            .request(Method::POST, checkout_link)
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(ZeroizingBody::from(std::mem::take(&mut *querystring)))
+            .build()?;
+
+        let response = self.http_client.execute(request)?;
+
+        if let Some(location) = checkout_location(response.status(), response.headers()) {
+            return CheckoutLink::from_location(checkout_link, &location, order_number, expires_at);
+        }
+
+        let status = response.status();
+        Err(checkout_error(status, response.text()?))
+    }
+
+    /// Returns an error if it fails to validate any of its arguments.
+    ///
+    /// See [`crate::YapaySDK::create_credit_card_payment`].
+    pub fn create_credit_card_payment(
+        &self,
+        customer: YapayCustomer,
+        transaction: YapayTransaction,
+        products: Vec<YapayProduct>,
+        cc_payment_data: YapayCardData,
+    ) -> Result<SDKJsonRequest<'_, CardTransactionResponse>, SDKError> {
+        let (payload, missing_finger_print) = credit_card_payload(
+            &self.account_token,
+            customer,
+            transaction,
+            products,
+            cc_payment_data,
+        )?;
+
+        let mut request =
+            SDKJsonRequest::from_zeroizing(self, Method::POST, PAYMENT_ENDPOINT, payload);
+        request.missing_finger_print = missing_finger_print;
+
+        Ok(request)
+    }
+
+    #[must_use]
+    pub fn simulate_payment(&self, total_amount: f64) -> SDKJsonRequest<'_, SimulationResponse> {
+        let payload = simulate_payload(&self.account_token, total_amount);
+        SDKJsonRequest::from_zeroizing(self, Method::POST, SIMULATE_ENDPOINT, payload)
+    }
+
+    /// Simulates `total_amount` only for `methods`, with up to `max_split` installments.
+    ///
+    /// See [`crate::YapaySDK::simulate_payment_for`].
+    pub fn simulate_payment_for<PM>(
+        &self,
+        total_amount: f64,
+        methods: &[PM],
+        max_split: u8,
+    ) -> Result<SDKJsonRequest<'_, SimulationResponse>, SDKError>
+    where
+        PM: AsPaymentMethod,
+    {
+        let payload =
+            restricted_simulate_payload(&self.account_token, total_amount, methods, max_split)?;
+        Ok(SDKJsonRequest::from_zeroizing(
+            self,
+            Method::POST,
+            SIMULATE_ENDPOINT,
+            payload,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::num::NonZeroU8;
+    use std::thread::{self, JoinHandle};
+
+    use super::*;
+    use crate::errors::InvalidError;
+    use crate::helpers::REDACTED;
+    use crate::PaymentCreditCard;
+
+    #[test]
+    fn t_blocking_debug_redacted() {
+        let sdk = YapaySDKBuilder::with_token(&"secret-account-token");
+        let request = sdk.simulate_payment(100.0);
+
+        let formatted = format!("{:?} {:#?}", sdk, request);
+        assert!(formatted.contains(REDACTED));
+        assert!(!formatted.contains("secret-account-token"));
+    }
+
+    /// Answers a single request with `response`, and returns its url and the request received.
+    fn serve_once(response: &'static str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "http://{}/v2/cart/index.php",
+            listener.local_addr().unwrap()
+        );
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut head = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(len) = line.to_lowercase().strip_prefix("content-length:") {
+                    content_length = len.trim().parse().unwrap();
+                }
+                head.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            head + &String::from_utf8(body).unwrap()
+        });
+
+        (url, server)
+    }
+
+    fn checkout_preferences() -> CheckoutPreferences {
+        let product = YapayProduct::new(
+            "note-100sk".to_string(),
+            "Notebook".to_string(),
+            NonZeroU8::new(1).unwrap(),
+            2453.5,
+        );
+        CheckoutPreferences::new("order-1".to_string(), vec![product]).unwrap()
+    }
+
+    #[test]
+    fn t_blocking_checkout_redirect() {
+        let sdk = YapaySDKBuilder::with_token(&"token");
+
+        // the redirect is not followed, the server would not answer a second request
+        let (url, server) = serve_once(
+            "HTTP/1.1 302 Found\r\nLocation: /payment/transaction/abc123\r\nContent-Length: \
+             0\r\nConnection: close\r\n\r\n",
+        );
+        let link = sdk.post_checkout(&url, checkout_preferences()).unwrap();
+        let request = server.join().unwrap();
+
+        assert_eq!(link.token(), "abc123");
+        assert_eq!(link.url().path(), "/payment/transaction/abc123");
+        assert_eq!(link.order_number(), "order-1");
+        assert!(request.starts_with("POST /v2/cart/index.php"));
+        assert!(request
+            .to_lowercase()
+            .contains("content-type: application/x-www-form-urlencoded"));
+        assert!(request.contains("token_account=token&order_number=order-1"));
+
+        let (url, server) =
+            serve_once("HTTP/1.1 200 OK\r\nContent-Length: 7\r\nConnection: close\r\n\r\nno cart");
+        let res = sdk.post_checkout(&url, checkout_preferences());
+        server.join().unwrap();
+        assert!(matches!(
+            res,
+            Err(SDKError::CheckoutError { status: 200, body }) if body == "no cart"
+        ));
+    }
+
+    #[test]
+    fn t_blocking_max_split() {
+        let sdk = YapaySDKBuilder::with_token(&"token");
+        let res = sdk.simulate_payment_for(100.0, &[PaymentCreditCard::Visa], 13);
+        assert!(matches!(
+            res,
+            Err(SDKError::InvalidError(InvalidError::PaymentTermsError(_)))
+        ));
+    }
+}
@@ -0,0 +1,659 @@
+//! Card data types, and the checks done before any card data is sent to Yapay.
+
+use std::fmt;
+use std::num::NonZeroU8;
+use std::str::FromStr;
+
+use time::{Date, Month};
+use zeroize::Zeroize;
+
+use crate::common_types::{PaymentCreditCard, YapayCardData, MAX_INSTALLMENTS};
+use crate::errors::{CreditCardError, InvalidError, SDKError};
+use crate::helpers::REDACTED;
+
+/// Known Elo BINs, as inclusive ranges of the first 6 digits. Some of them overlap Visa and
+/// Mastercard, so Elo must be matched first.
+///
+/// Issuers keep adding BINs, so this and the Hipercard tables are not exhaustive.
+const ELO_BINS: &[(u32, u32)] = &[
+    (401_178, 401_179),
+    (431_274, 431_274),
+    (438_935, 438_935),
+    (451_416, 451_416),
+    (457_393, 457_393),
+    (457_631, 457_632),
+    (504_175, 504_175),
+    (506_699, 506_778),
+    (509_000, 509_999),
+    (627_780, 627_780),
+    (636_297, 636_297),
+    (636_368, 636_368),
+    (650_031, 650_033),
+    (650_035, 650_051),
+    (650_405, 650_439),
+    (650_485, 650_538),
+    (650_541, 650_598),
+    (650_700, 650_718),
+    (650_720, 650_727),
+    (650_901, 650_978),
+    (651_652, 651_679),
+    (655_000, 655_019),
+    (655_021, 655_058),
+];
+
+const HIPERCARD_BINS: &[u32] = &[606_282, 384_100, 384_140, 384_160];
+
+const HIPER_ITAU_BINS: &[u32] = &[637_095, 637_568, 637_599, 637_609, 637_612];
+
+/// Removes the spaces and dashes card numbers are usually typed with.
+pub(crate) fn normalize_card_number(raw: &str) -> String {
+    raw.chars()
+        .filter(|char| !matches!(char, ' ' | '-'))
+        .collect()
+}
+
+/// Keeps only the first 6 and last 4 digits, as allowed by PCI DSS. Shorter numbers are fully
+/// masked.
+pub(crate) fn mask_card_number(number: &str) -> String {
+    let len = number.chars().count();
+    if len < 13 {
+        return "*".repeat(len);
+    }
+
+    number
+        .chars()
+        .enumerate()
+        .map(
+            |(idx, char)| {
+                if idx < 6 || idx >= len - 4 {
+                    char
+                } else {
+                    '*'
+                }
+            },
+        )
+        .collect()
+}
+
+/// Whether `number` has only digits and passes the Luhn checksum.
+// `u32::is_multiple_of` needs Rust 1.87
+#[allow(clippy::manual_is_multiple_of)]
+pub fn luhn_check(number: &str) -> bool {
+    if number.is_empty() || !number.chars().all(|char| char.is_ascii_digit()) {
+        return false;
+    }
+
+    let sum: u32 = number
+        .bytes()
+        .rev()
+        .map(|byte| u32::from(byte - b'0'))
+        .enumerate()
+        .map(|(idx, digit)| match (idx % 2, digit * 2) {
+            (0, _) => digit,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+
+    sum % 10 == 0
+}
+
+impl PaymentCreditCard {
+    /// Detects the brand from the BIN, the first digits of a normalized card number.
+    pub fn from_card_number(number: &str) -> Option<Self> {
+        let prefix = |len: usize| {
+            number
+                .get(..len)
+                .and_then(|digits| digits.parse::<u32>().ok())
+        };
+        if let Some(brand) = Self::from_bin_tables(number) {
+            return Some(brand);
+        }
+
+        match (prefix(1)?, prefix(2)?, prefix(4)?) {
+            (_, 34 | 37, _) => Some(Self::Amex),
+            (_, 51..=55, _) | (_, _, 2221..=2720) => Some(Self::MasterCard),
+            (4, _, _) => Some(Self::Visa),
+            _ => None,
+        }
+    }
+
+    /// The brand of `number` from the known Elo and Hipercard BINs.
+    fn from_bin_tables(number: &str) -> Option<Self> {
+        let bin = number.get(..6)?.parse::<u32>().ok()?;
+
+        if ELO_BINS
+            .iter()
+            .any(|&(start, end)| (start..=end).contains(&bin))
+        {
+            Some(Self::Elo)
+        } else if HIPERCARD_BINS.contains(&bin) {
+            Some(Self::HiperCard)
+        } else if HIPER_ITAU_BINS.contains(&bin) {
+            Some(Self::HiperItau)
+        } else {
+            None
+        }
+    }
+
+    /// Number of digits of the security code.
+    pub const fn cvv_length(self) -> usize {
+        match self {
+            Self::Amex => 4,
+            _ => 3,
+        }
+    }
+}
+
+/// Checks the number with Luhn, its brand against `brand`, and the CVV length of that brand.
+///
+/// Only a number known to be from another brand is a mismatch. Unknown BINs are accepted, and so
+/// are Elo and Hipercard numbers outside of their BIN tables, which may look like Visa or
+/// Mastercard.
+///
+/// `card_number` must be already normalized.
+pub(crate) fn validate_card(
+    brand: PaymentCreditCard,
+    card_number: &str,
+    card_cvv: &str,
+) -> Result<(), CreditCardError> {
+    if !(13..=19).contains(&card_number.len()) || !luhn_check(card_number) {
+        return Err(CreditCardError::InvalidNumber);
+    }
+
+    let partial_bins = matches!(
+        brand,
+        PaymentCreditCard::Elo | PaymentCreditCard::HiperCard | PaymentCreditCard::HiperItau
+    );
+    match PaymentCreditCard::from_card_number(card_number) {
+        None => {}
+        Some(detected) if detected == brand => {}
+        Some(_) if partial_bins && PaymentCreditCard::from_bin_tables(card_number).is_none() => {}
+        detected => {
+            return Err(CreditCardError::BrandMismatch {
+                expected: brand,
+                detected,
+            })
+        }
+    }
+
+    if card_cvv.len() != brand.cvv_length() || !card_cvv.chars().all(|char| char.is_ascii_digit()) {
+        return Err(CreditCardError::InvalidCvv {
+            expected_length: brand.cvv_length(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Number of installments, from 1 to [`MAX_INSTALLMENTS`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Installments(NonZeroU8);
+
+impl Installments {
+    pub const ONE: Self = Self(NonZeroU8::MIN);
+
+    /// [`None`] when `installments` is 0 or more than [`MAX_INSTALLMENTS`].
+    pub const fn new(installments: u8) -> Option<Self> {
+        match NonZeroU8::new(installments) {
+            Some(installments) if installments.get() <= MAX_INSTALLMENTS => {
+                Some(Self(installments))
+            }
+            _ => None,
+        }
+    }
+
+    pub const fn get(self) -> u8 {
+        self.0.get()
+    }
+}
+
+impl Default for Installments {
+    fn default() -> Self {
+        Self::ONE
+    }
+}
+
+impl fmt::Display for Installments {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Month of the card expiration, from 1 to 12. Displayed as `MM`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CardMonth(u8);
+
+impl CardMonth {
+    pub const fn new(month: u8) -> Option<Self> {
+        match month {
+            1..=12 => Some(Self(month)),
+            _ => None,
+        }
+    }
+
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+impl fmt::Display for CardMonth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}", self.0)
+    }
+}
+
+/// Year of the card expiration, with 4 digits. Displayed as `YYYY`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CardYear(u16);
+
+impl CardYear {
+    pub const fn new(year: u16) -> Option<Self> {
+        match year {
+            2000..=9999 => Some(Self(year)),
+            _ => None,
+        }
+    }
+
+    pub const fn get(self) -> u16 {
+        self.0
+    }
+}
+
+impl fmt::Display for CardYear {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Card expiration. A card is valid through the last day of its expiration month.
+///
+/// Parsed from `MM/YY` or `MM/YYYY`, or from separate fields with [`CardExpiry::from_parts`].
+///
+/// # Usage
+///
+/// ```
+/// use time::macros::date;
+/// use yapay_sdk_rust::CardExpiry;
+///
+/// let expiry: CardExpiry = "05/22".parse().unwrap();
+/// assert_eq!(expiry, CardExpiry::from_parts("5", "2022").unwrap());
+///
+/// assert!(expiry.is_valid_on(date!(2022 - 05 - 31)));
+/// assert!(!expiry.is_valid_on(date!(2022 - 06 - 01)));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CardExpiry {
+    year: CardYear,
+    month: CardMonth,
+}
+
+impl CardExpiry {
+    pub const fn new(month: CardMonth, year: CardYear) -> Self {
+        Self { year, month }
+    }
+
+    /// Month with 1 or 2 digits, and year with 2 or 4 digits, where `YY` means `20YY`.
+    pub fn from_parts(month: &str, year: &str) -> Result<Self, CreditCardError> {
+        let (month, year) = (month.trim(), year.trim());
+
+        if !(1..=2).contains(&month.len()) || !matches!(year.len(), 2 | 4) {
+            return Err(CreditCardError::InvalidExpiration);
+        }
+
+        let month = month
+            .parse::<u8>()
+            .ok()
+            .and_then(CardMonth::new)
+            .ok_or(CreditCardError::InvalidExpiration)?;
+        let year = year
+            .parse::<u16>()
+            .ok()
+            .map(|year| if year < 100 { 2000 + year } else { year })
+            .and_then(CardYear::new)
+            .ok_or(CreditCardError::InvalidExpiration)?;
+
+        Ok(Self::new(month, year))
+    }
+
+    pub const fn month(self) -> CardMonth {
+        self.month
+    }
+
+    pub const fn year(self) -> CardYear {
+        self.year
+    }
+
+    /// Last day the card can be used.
+    pub fn last_day(self) -> Date {
+        let month = Month::try_from(self.month.get()).expect("Month from 1 to 12.");
+        let year = i32::from(self.year.get());
+
+        Date::from_calendar_date(year, month, month.length(year)).expect("Valid date.")
+    }
+
+    pub fn is_valid_on(self, date: Date) -> bool {
+        date <= self.last_day()
+    }
+}
+
+impl FromStr for CardExpiry {
+    type Err = CreditCardError;
+
+    fn from_str(expiry: &str) -> Result<Self, Self::Err> {
+        let (month, year) = expiry
+            .split_once('/')
+            .ok_or(CreditCardError::InvalidExpiration)?;
+
+        Self::from_parts(month, year)
+    }
+}
+
+/// Displayed as `MM/YYYY`.
+impl fmt::Display for CardExpiry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.month, self.year)
+    }
+}
+
+/// Builds a [`YapayCardData`] with named setters. Everything is validated at
+/// [`YapayCardDataBuilder::build`].
+///
+/// # Usage
+///
+/// ```
+/// use yapay_sdk_rust::{Installments, PaymentCreditCard, YapayCardData};
+///
+/// let card = YapayCardData::builder()
+///     .brand(PaymentCreditCard::MasterCard)
+///     .holder_name("Joaquim Silva")
+///     .number("5555 6666 7777 8884")
+///     .expiration("06/99".parse().unwrap())
+///     .cvv("411")
+///     .installments(Installments::new(3).unwrap())
+///     .build()
+///     .expect("valid card");
+/// ```
+#[derive(Default)]
+pub struct YapayCardDataBuilder {
+    brand: Option<PaymentCreditCard>,
+    holder_name: Option<String>,
+    number: Option<String>,
+    expiration: Option<CardExpiry>,
+    cvv: Option<String>,
+    installments: Installments,
+    finger_print: Option<String>,
+}
+
+impl YapayCardDataBuilder {
+    #[must_use]
+    pub fn brand(mut self, brand: PaymentCreditCard) -> Self {
+        self.brand = Some(brand);
+        self
+    }
+
+    /// Name printed on the card.
+    #[must_use]
+    pub fn holder_name(mut self, holder_name: impl Into<String>) -> Self {
+        self.holder_name = Some(holder_name.into());
+        self
+    }
+
+    /// Spaces and dashes are removed.
+    #[must_use]
+    pub fn number(mut self, number: impl Into<String>) -> Self {
+        self.number = Some(number.into());
+        self
+    }
+
+    #[must_use]
+    pub fn expiration(mut self, expiration: CardExpiry) -> Self {
+        self.expiration = Some(expiration);
+        self
+    }
+
+    #[must_use]
+    pub fn cvv(mut self, cvv: impl Into<String>) -> Self {
+        self.cvv = Some(cvv.into());
+        self
+    }
+
+    /// Defaults to a single installment.
+    #[must_use]
+    pub fn installments(mut self, installments: Installments) -> Self {
+        self.installments = installments;
+        self
+    }
+
+    /// See [`YapayCardData::set_finger_print`].
+    #[must_use]
+    pub fn finger_print(mut self, session_id: impl Into<String>) -> Self {
+        self.finger_print = Some(session_id.into());
+        self
+    }
+
+    pub fn build(mut self) -> Result<YapayCardData, SDKError> {
+        let brand = self.brand.ok_or(InvalidError::MissingField("brand"))?;
+        let holder_name = self
+            .holder_name
+            .take()
+            .ok_or(InvalidError::MissingField("holder_name"))?;
+        let number = self
+            .number
+            .take()
+            .ok_or(InvalidError::MissingField("number"))?;
+        let expiration = self
+            .expiration
+            .ok_or(InvalidError::MissingField("expiration"))?;
+        let cvv = self.cvv.take().ok_or(InvalidError::MissingField("cvv"))?;
+
+        let card = YapayCardData::new(
+            brand,
+            holder_name,
+            number,
+            expiration.month().to_string(),
+            expiration.year().to_string(),
+            cvv,
+            i8::try_from(self.installments.get()).expect("Capped at 12."),
+        )?;
+
+        Ok(match self.finger_print.take() {
+            Some(session_id) => card.set_finger_print(session_id),
+            None => card,
+        })
+    }
+}
+
+impl Drop for YapayCardDataBuilder {
+    fn drop(&mut self) {
+        self.holder_name.zeroize();
+        self.number.zeroize();
+        self.cvv.zeroize();
+    }
+}
+
+impl fmt::Debug for YapayCardDataBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("YapayCardDataBuilder")
+            .field("brand", &self.brand)
+            .field("holder_name", &self.holder_name)
+            .field(
+                "number",
+                &self
+                    .number
+                    .as_deref()
+                    .map(|number| mask_card_number(&normalize_card_number(number))),
+            )
+            .field("expiration", &self.expiration)
+            .field("cvv", &self.cvv.as_ref().map(|_| REDACTED))
+            .field("installments", &self.installments)
+            .field("finger_print", &self.finger_print)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_luhn() {
+        assert!(luhn_check("4111111111111111"));
+        assert!(luhn_check("5555666677778884"));
+        assert!(!luhn_check("3456856415487894"));
+        assert!(!luhn_check("4111 1111 1111 1111"));
+        assert!(!luhn_check(""));
+    }
+
+    #[test]
+    fn t_normalize() {
+        assert_eq!(
+            normalize_card_number("4111 1111-1111 1111"),
+            "4111111111111111"
+        );
+    }
+
+    #[test]
+    fn t_mask() {
+        assert_eq!(mask_card_number("4111111111111111"), "411111******1111");
+        assert_eq!(mask_card_number("378282246310005"), "378282*****0005");
+        assert_eq!(mask_card_number("41111"), "*****");
+    }
+
+    #[test]
+    fn t_detect_brand() {
+        for (number, brand) in [
+            ("4111111111111111", PaymentCreditCard::Visa),
+            ("5555666677778884", PaymentCreditCard::MasterCard),
+            ("2221000000000009", PaymentCreditCard::MasterCard),
+            ("378282246310005", PaymentCreditCard::Amex),
+            ("6362970000457013", PaymentCreditCard::Elo),
+            ("4514160123456789", PaymentCreditCard::Elo),
+            ("6062825624254001", PaymentCreditCard::HiperCard),
+            ("6370950000000005", PaymentCreditCard::HiperItau),
+        ] {
+            assert_eq!(PaymentCreditCard::from_card_number(number), Some(brand));
+        }
+        assert_eq!(
+            PaymentCreditCard::from_card_number("9999999999999995"),
+            None
+        );
+    }
+
+    #[test]
+    fn t_installments() {
+        assert_eq!(Installments::new(1), Some(Installments::ONE));
+        assert_eq!(Installments::new(12).map(Installments::get), Some(12));
+        assert_eq!(Installments::new(0), None);
+        assert_eq!(Installments::new(13), None);
+    }
+
+    #[test]
+    fn t_month_year() {
+        assert_eq!(CardMonth::new(6).unwrap().to_string(), "06");
+        assert_eq!(CardMonth::new(13), None);
+        assert_eq!(CardYear::new(2030).unwrap().to_string(), "2030");
+        assert_eq!(CardYear::new(30), None);
+    }
+
+    fn builder() -> YapayCardDataBuilder {
+        YapayCardData::builder()
+            .brand(PaymentCreditCard::MasterCard)
+            .holder_name("Joaquim Silva")
+            .number("5555 6666 7777 8884")
+            .expiration(CardExpiry::new(
+                CardMonth::new(6).unwrap(),
+                CardYear::new(2099).unwrap(),
+            ))
+            .cvv("411")
+    }
+
+    #[test]
+    fn t_card_builder() {
+        let card = builder()
+            .installments(Installments::new(3).unwrap())
+            .finger_print("session-id")
+            .build()
+            .unwrap();
+
+        assert_eq!(card.card_number, "5555666677778884");
+        assert_eq!(card.card_expdate_month, "06");
+        assert_eq!(card.card_expdate_year, "2099");
+        assert_eq!(card.split, "3");
+        assert_eq!(card.finger_print, "session-id");
+
+        let formatted = format!("{:?}", builder());
+        assert!(!formatted.contains("5555 6666 7777 8884"));
+        assert!(!formatted.contains("411"));
+    }
+
+    #[test]
+    fn t_card_builder_missing_field() {
+        let res = YapayCardData::builder()
+            .brand(PaymentCreditCard::Visa)
+            .build();
+        assert!(matches!(
+            res,
+            Err(SDKError::InvalidError(InvalidError::MissingField(
+                "holder_name"
+            )))
+        ));
+
+        assert!(builder().brand(PaymentCreditCard::Visa).build().is_err());
+    }
+
+    #[test]
+    fn t_card_expiry() {
+        let expiry = CardExpiry::from_parts("05", "2022").unwrap();
+        for raw in ["05/22", "5/22", "05/2022", " 05 / 2022 "] {
+            assert_eq!(raw.parse::<CardExpiry>().unwrap(), expiry);
+        }
+        assert_eq!(expiry.to_string(), "05/2022");
+        assert_eq!(expiry.last_day(), time::macros::date!(2022 - 05 - 31));
+
+        for raw in ["13/22", "00/22", "05/222", "0522", "05/", "ab/cd"] {
+            assert!(raw.parse::<CardExpiry>().is_err(), "{} parsed", raw);
+        }
+    }
+
+    #[test]
+    fn t_card_expiry_end_of_month() {
+        let expiry: CardExpiry = "02/24".parse().unwrap();
+        assert!(expiry.is_valid_on(time::macros::date!(2024 - 02 - 29)));
+        assert!(!expiry.is_valid_on(time::macros::date!(2024 - 03 - 01)));
+    }
+
+    #[test]
+    fn t_validate_card() {
+        assert!(validate_card(PaymentCreditCard::Visa, "4111111111111111", "123").is_ok());
+        assert!(validate_card(PaymentCreditCard::Amex, "378282246310005", "1234").is_ok());
+
+        assert!(matches!(
+            validate_card(PaymentCreditCard::Visa, "4111111111111112", "123"),
+            Err(CreditCardError::InvalidNumber)
+        ));
+        assert!(matches!(
+            validate_card(PaymentCreditCard::MasterCard, "4111111111111111", "123"),
+            Err(CreditCardError::BrandMismatch {
+                detected: Some(PaymentCreditCard::Visa),
+                ..
+            })
+        ));
+        assert!(matches!(
+            validate_card(PaymentCreditCard::Visa, "6362970000457013", "123"),
+            Err(CreditCardError::BrandMismatch {
+                detected: Some(PaymentCreditCard::Elo),
+                ..
+            })
+        ));
+
+        // Elo BIN missing from the table, and an unknown BIN
+        assert!(validate_card(PaymentCreditCard::Elo, "4111111111111111", "123").is_ok());
+        assert!(validate_card(PaymentCreditCard::Visa, "9999999999999995", "123").is_ok());
+
+        assert!(matches!(
+            validate_card(PaymentCreditCard::Amex, "378282246310005", "123"),
+            Err(CreditCardError::InvalidCvv { expected_length: 4 })
+        ));
+    }
+}
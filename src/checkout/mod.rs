@@ -0,0 +1,731 @@
+mod registry;
+
+use std::fmt::{Display, Formatter};
+
+use reqwest::IntoUrl;
+use serde::Serialize;
+use time::OffsetDateTime;
+use url::Url;
+use validator::Validate;
+use zeroize::Zeroizing;
+
+pub use self::registry::{CheckoutRegistry, CheckoutVerdict};
+use crate::common_types::{order_total, AsPaymentMethod, PaymentTerms};
+use crate::helpers::format_available_payment_method;
+use crate::secret::to_zeroizing_form;
+use crate::{InvalidError, SDKError, YapayCustomer, YapayProduct};
+
+/// Preferences to create your checkout.
+///
+///
+/// [`notification_url`] is optional, but highly recommended way to receive payment updates.
+///
+///
+/// [`available_payment_methods`] is used to restrict payment options, and can be used with the
+/// `set_available_payment_methods` builder.
+///
+///
+/// [`customer`] and the shipping fields are optional, and pre-fill the hosted checkout for
+/// returning buyers.
+///
+///
+/// [`expires_at`] is not sent to Yapay, as its checkout has no expiration. It is carried into the
+/// [`CheckoutLink`], to be enforced by a [`CheckoutRegistry`].
+#[derive(Validate, Default, Debug, Clone, PartialEq, Serialize)]
+pub struct CheckoutPreferences {
+    order_number: String,
+
+    transaction_products: Vec<YapayProduct>,
+
+    /// URL para redirecionamento caso concluída a transação com sucesso
+    url_success: Option<String>,
+    /// URL para redirecionamento caso pedido esteja aguardando a confirmação de pagamento
+    url_process: Option<String>,
+    /// URL para redirecionamento caso concluída a transação mas ocorreu falha no pagamento
+    url_cancel: Option<String>,
+
+    /// URL para onde deve notificado após mudanças no status de pagamento.
+    notification_url: Option<String>,
+
+    /// Meios de pagamento disponíveis, separados por vírgula.
+    available_payment_methods: Option<String>,
+
+    /// Dados do comprador, para preencher o checkout.
+    #[validate]
+    customer: Option<YapayCustomer>,
+
+    /// Tipo de envio. Exemplo: Sedex
+    shipping_type: Option<String>,
+    /// Valor do envio.
+    shipping_price: Option<String>,
+
+    /// Desconto, acréscimo e limites de parcelamento.
+    payment_terms: Option<PaymentTerms>,
+
+    /// Quando o link de checkout deixa de valer. Controlado pelo SDK, não pelo Yapay.
+    #[serde(skip)]
+    expires_at: Option<OffsetDateTime>,
+}
+
+impl CheckoutPreferences {
+    pub fn new(order_number: String, products: Vec<YapayProduct>) -> Result<Self, SDKError> {
+        let builder = Self {
+            order_number,
+            transaction_products: products,
+            url_success: None,
+            url_process: None,
+            url_cancel: None,
+            notification_url: None,
+            available_payment_methods: None,
+            customer: None,
+            shipping_type: None,
+            shipping_price: None,
+            payment_terms: None,
+            expires_at: None,
+        };
+
+        if let Err(err) = builder.validate() {
+            return Err(InvalidError::ValidatorLibError(err).into());
+        }
+
+        Ok(builder)
+    }
+
+    /// Restricts payment methods to those used on `methods` parameter.
+    ///
+    ///
+    /// # Usage
+    ///
+    /// ```
+    /// use std::num::NonZeroU8;
+    ///
+    /// use yapay_sdk_rust::{AsPaymentMethod, CheckoutPreferences, PaymentCreditCard, YapayProduct};
+    ///
+    /// let product = YapayProduct::new(
+    ///     "sample".to_string(),
+    ///     "a sample product".to_string(),
+    ///     NonZeroU8::new(1).unwrap(),
+    ///     10_f64,
+    /// );
+    ///
+    /// let preferences = CheckoutPreferences::new("order_number".to_string(), vec![product])
+    ///     .unwrap()
+    ///     .set_available_payment_methods(&[PaymentCreditCard::Visa, PaymentCreditCard::MasterCard]);
+    ///
+    /// // now you can only pay with credit cards
+    /// ```
+    #[must_use]
+    pub fn set_available_payment_methods<PM>(mut self, methods: &[PM]) -> Self
+    where
+        PM: AsPaymentMethod,
+    {
+        self.available_payment_methods = Some(format_available_payment_method(methods));
+        self
+    }
+
+    pub fn set_notification_url<U>(mut self, url: U) -> Result<Self, SDKError>
+    where
+        U: IntoUrl,
+    {
+        self.notification_url = Some(validate_url(url)?);
+        Ok(self)
+    }
+
+    /// Sets the `url_success`, which will redirect the user after a successful payment.
+    pub fn set_success_url<U>(mut self, url: U) -> Result<Self, SDKError>
+    where
+        U: IntoUrl,
+    {
+        self.url_success = Some(validate_url(url)?);
+        Ok(self)
+    }
+
+    /// Sets the `url_process`, which will redirect the user after payment.
+    /// This is the standard way of redirecting, it doesn't matter if the transaction failed, or was
+    /// a success.
+    ///
+    ///
+    /// You can use this to trigger a response to your server that the user has finished a payment,
+    /// and now needs to wait for a definitive response.
+    pub fn set_process_url<U>(mut self, url: U) -> Result<Self, SDKError>
+    where
+        U: IntoUrl,
+    {
+        self.url_process = Some(validate_url(url)?);
+        Ok(self)
+    }
+
+    /// Sets the `url_cancel`, which will redirect the user when the transaction is completed but
+    /// the payment failed.
+    pub fn set_cancel_url<U>(mut self, url: U) -> Result<Self, SDKError>
+    where
+        U: IntoUrl,
+    {
+        self.url_cancel = Some(validate_url(url)?);
+        Ok(self)
+    }
+
+    /// Pre-fills the hosted checkout with the `customer` name, documents, contacts and addresses.
+    #[must_use]
+    pub fn set_customer(mut self, customer: YapayCustomer) -> Self {
+        self.customer = Some(customer);
+        self
+    }
+
+    /// Sets the shipping type, e.g. "Sedex", and its price, which can't be negative.
+    pub fn set_shipping(
+        mut self,
+        shipping_type: &str,
+        shipping_price: f64,
+    ) -> Result<Self, SDKError> {
+        if !shipping_price.is_finite() || shipping_price < 0.0 {
+            return Err(InvalidError::ShippingPriceError(shipping_price).into());
+        }
+
+        self.shipping_type = Some(shipping_type.to_string());
+        self.shipping_price = Some(shipping_price.to_string());
+        Ok(self)
+    }
+
+    /// Applies discount, additional fees and installment limits, after validating them against
+    /// the total of products plus shipping.
+    ///
+    /// Set the shipping before calling this, so it counts towards the order total.
+    pub fn set_payment_terms(mut self, terms: PaymentTerms) -> Result<Self, SDKError> {
+        terms.validate_for(order_total(
+            &self.transaction_products,
+            self.shipping_price.as_deref(),
+        ))?;
+
+        self.payment_terms = Some(terms);
+        Ok(self)
+    }
+
+    /// Sets when the checkout link stops working, e.g. for flash sales or reserved stock.
+    ///
+    /// Yapay's hosted checkout has no expiration of its own, so this is only enforced when the
+    /// returned [`CheckoutLink`] is registered on a [`CheckoutRegistry`].
+    pub fn set_expiration(mut self, expires_at: OffsetDateTime) -> Result<Self, SDKError> {
+        if expires_at <= OffsetDateTime::now_utc() {
+            return Err(InvalidError::CheckoutExpirationError.into());
+        }
+
+        self.expires_at = Some(expires_at);
+        Ok(self)
+    }
+
+    pub(crate) fn order_number(&self) -> &str {
+        &self.order_number
+    }
+
+    pub(crate) fn expires_at(&self) -> Option<OffsetDateTime> {
+        self.expires_at
+    }
+
+    /// Serializes the preferences into the `application/x-www-form-urlencoded` body the hosted
+    /// checkout expects. Every key and value is percent-encoded.
+    ///
+    /// The body carries the account token, so it is wiped on drop.
+    #[must_use]
+    pub fn to_form(self, token: &str) -> Zeroizing<String> {
+        let mut base_vec = vec![("order_number", self.order_number)];
+
+        for item in self.transaction_products {
+            base_vec.push(("transaction_product[][description]", item.description));
+            base_vec.push(("transaction_product[][quantity]", item.quantity));
+            base_vec.push(("transaction_product[][price_unit]", item.price_unit));
+            if !item.code.is_empty() {
+                base_vec.push(("transaction_product[][code]", item.code));
+            }
+            if !item.sku_code.is_empty() {
+                base_vec.push(("transaction_product[][sku_code]", item.sku_code));
+            }
+            if let Some(extra) = item.extra {
+                base_vec.push(("transaction_product[][extra]", extra));
+            }
+        }
+
+        if let Some(url) = self.notification_url {
+            base_vec.push(("notification_url", url));
+        }
+
+        if let Some(payment_methods) = self.available_payment_methods {
+            base_vec.push(("available_payment_methods", payment_methods));
+        }
+
+        if let Some(customer) = self.customer {
+            base_vec.extend(customer_form(customer));
+        }
+
+        if let Some(shipping_type) = self.shipping_type {
+            base_vec.push(("shipping_type", shipping_type));
+        }
+
+        if let Some(shipping_price) = self.shipping_price {
+            base_vec.push(("shipping_price", shipping_price));
+        }
+
+        if let Some(terms) = self.payment_terms {
+            base_vec.extend(terms.fields());
+        }
+
+        if let Some(url) = self.url_success {
+            base_vec.push(("url_success", url));
+        }
+
+        if let Some(url) = self.url_process {
+            base_vec.push(("url_process", url));
+        }
+
+        if let Some(url) = self.url_cancel {
+            base_vec.push(("url_cancel", url));
+        }
+
+        let pairs = base_vec.iter().map(|(key, value)| (*key, value.as_str()));
+        to_zeroizing_form(std::iter::once(("token_account", token)).chain(pairs))
+    }
+}
+
+/// The hosted checkout page created by `YapaySDK::create_checkout_page`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckoutLink {
+    url: Url,
+    token: String,
+    order_number: String,
+    expires_at: Option<OffsetDateTime>,
+}
+
+impl CheckoutLink {
+    /// Parses the `Location` header Yapay answers with, resolved against `checkout_base`, the
+    /// url the checkout was posted to.
+    ///
+    /// The checkout token comes either in the `token_transaction` query parameter, or as the
+    /// segment after `/payment/transaction/`.
+    pub(crate) fn from_location(
+        checkout_base: &str,
+        location: &str,
+        order_number: String,
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<Self, SDKError> {
+        let url = Url::parse(checkout_base)
+            .and_then(|base| base.join(location))
+            .map_err(|e| InvalidError::CheckoutLinkError(format!("{}: {}", location, e)))?;
+
+        let from_query = url
+            .query_pairs()
+            .find(|(key, _)| key == "token_transaction")
+            .map(|(_, value)| value.into_owned());
+        let from_path = url.path_segments().and_then(|segments| {
+            match segments.collect::<Vec<_>>().as_slice() {
+                ["payment", "transaction", token] => Some(token.to_string()),
+                _ => None,
+            }
+        });
+
+        let token = from_query
+            .or(from_path)
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| {
+                InvalidError::CheckoutLinkError(format!("{}: missing token", location))
+            })?;
+
+        Ok(Self {
+            url,
+            token,
+            order_number,
+            expires_at,
+        })
+    }
+
+    /// Where your customer should be redirected to.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Token of the checkout, embedded in its url.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn order_number(&self) -> &str {
+        &self.order_number
+    }
+
+    /// Set with [`CheckoutPreferences::set_expiration`].
+    pub fn expires_at(&self) -> Option<OffsetDateTime> {
+        self.expires_at
+    }
+
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.expires_at.is_some_and(|expires_at| now > expires_at)
+    }
+
+    pub fn into_url(self) -> Url {
+        self.url
+    }
+}
+
+impl Display for CheckoutLink {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.url)
+    }
+}
+
+fn customer_form(customer: YapayCustomer) -> Vec<(&'static str, String)> {
+    let mut form = vec![
+        ("customer[name]", customer.name),
+        ("customer[cpf]", customer.cpf),
+        ("customer[email]", customer.email),
+        ("customer[birth_date]", customer.birth_date),
+    ];
+
+    if let Some(cnpj) = customer.cnpj {
+        form.push(("customer[cnpj]", cnpj));
+    }
+
+    for contact in customer.contacts {
+        form.push((
+            "customer[contacts][][type_contact]",
+            contact.type_contact.code().to_string(),
+        ));
+        form.push((
+            "customer[contacts][][number_contact]",
+            contact.number_contact,
+        ));
+    }
+
+    for address in customer.addresses {
+        form.push((
+            "customer[addresses][][type_address]",
+            address.type_address.code().to_string(),
+        ));
+        form.push(("customer[addresses][][postal_code]", address.postal_code));
+        form.push(("customer[addresses][][street]", address.street));
+        form.push(("customer[addresses][][number]", address.number));
+        form.push(("customer[addresses][][completion]", address.completion));
+        form.push(("customer[addresses][][neighborhood]", address.neighborhood));
+        form.push(("customer[addresses][][city]", address.city));
+        form.push(("customer[addresses][][state]", address.state));
+    }
+
+    form
+}
+
+fn validate_url<U>(url: U) -> Result<String, SDKError>
+where
+    U: IntoUrl,
+{
+    url.into_url()
+        .map(|a| a.as_str().to_string())
+        .map_err(|e| InvalidError::URLError(e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU8;
+
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::{
+        AddressType, CustomerAddress, CustomerPhoneContact, PaymentCreditCard, PhoneContactType,
+        YapayEnv,
+    };
+
+    fn parse_form(form: &str) -> Vec<(String, String)> {
+        url::form_urlencoded::parse(form.as_bytes())
+            .into_owned()
+            .collect()
+    }
+
+    fn sample_product() -> YapayProduct {
+        let mut product = YapayProduct::new(
+            "note-100sk".to_string(),
+            "Notebook".to_string(),
+            NonZeroU8::new(2).unwrap(),
+            2453.5,
+        );
+        product.extra = Some("Cinza".to_string());
+        product
+    }
+
+    #[test]
+    fn t_form_minimal() {
+        let mut product = sample_product();
+        product.code = String::new();
+        product.sku_code = String::new();
+        product.extra = None;
+
+        let form = CheckoutPreferences::new("order-1".to_string(), vec![product])
+            .unwrap()
+            .to_form("token");
+
+        assert_eq!(
+            *form,
+            "token_account=token&order_number=order-1&transaction_product%5B%5D%5Bdescription%\
+             5D=Notebook&transaction_product%5B%5D%5Bquantity%5D=2&transaction_product%5B%5D%\
+             5Bprice_unit%5D=2453.5"
+        );
+    }
+
+    #[test]
+    fn t_form_all_fields() {
+        let form = CheckoutPreferences::new("order-1".to_string(), vec![sample_product()])
+            .unwrap()
+            .set_notification_url("https://shop.com/notification")
+            .unwrap()
+            .set_available_payment_methods(&[PaymentCreditCard::Visa, PaymentCreditCard::Elo])
+            .set_success_url("https://shop.com/success")
+            .unwrap()
+            .set_process_url("https://shop.com/process")
+            .unwrap()
+            .set_cancel_url("https://shop.com/cancel")
+            .unwrap()
+            .to_form("token");
+
+        assert_eq!(
+            *form,
+            "token_account=token&order_number=order-1&transaction_product%5B%5D%5Bdescription%\
+             5D=Notebook&transaction_product%5B%5D%5Bquantity%5D=2&transaction_product%5B%5D%\
+             5Bprice_unit%5D=2453.5&transaction_product%5B%5D%5Bcode%5D=note-100sk&\
+             transaction_product%5B%5D%5Bsku_code%5D=note-100sk&transaction_product%5B%5D%5Bextra%\
+             5D=Cinza&notification_url=https%3A%2F%2Fshop.com%2Fnotification&\
+             available_payment_methods=3%2C16&url_success=https%3A%2F%2Fshop.com%2Fsuccess&\
+             url_process=https%3A%2F%2Fshop.com%2Fprocess&url_cancel=https%3A%2F%2Fshop.com%\
+             2Fcancel"
+        );
+    }
+
+    #[test]
+    fn t_form_escapes_values() {
+        let mut product = sample_product();
+        product.description = "Máquina & Cia = 100%".to_string();
+
+        let form = CheckoutPreferences::new("order-1".to_string(), vec![product])
+            .unwrap()
+            .set_notification_url("https://shop.com/notification?store=1&env=prod")
+            .unwrap()
+            .to_form("token");
+
+        let pairs = parse_form(&form);
+        assert!(pairs.contains(&(
+            "transaction_product[][description]".to_string(),
+            "Máquina & Cia = 100%".to_string()
+        )));
+        assert!(pairs.contains(&(
+            "notification_url".to_string(),
+            "https://shop.com/notification?store=1&env=prod".to_string()
+        )));
+    }
+
+    #[test]
+    fn t_form_prefilled_customer() {
+        let customer = YapayCustomer::new(
+            "Rufino Beltrano".to_string(),
+            "41810420814".to_string(),
+            "rufino@email.com".to_string(),
+            "11/01/2000".to_string(),
+            vec![CustomerPhoneContact {
+                type_contact: PhoneContactType::Celular,
+                number_contact: "11976914920".to_string(),
+            }],
+            vec![CustomerAddress {
+                type_address: AddressType::Entrega,
+                postal_code: "07097420".to_string(),
+                street: "Av Bartholomeu de carlos".to_string(),
+                number: "901".to_string(),
+                completion: "".to_string(),
+                neighborhood: "Jardim Flor da Montanha".to_string(),
+                city: "Guarulhos".to_string(),
+                state: "SP".to_string(),
+            }],
+        )
+        .unwrap();
+
+        let form = CheckoutPreferences::new("order-1".to_string(), vec![sample_product()])
+            .unwrap()
+            .set_customer(customer)
+            .set_shipping("Sedex", 12.5)
+            .unwrap()
+            .to_form("token");
+
+        let pairs = parse_form(&form);
+        let expected = [
+            ("customer[name]", "Rufino Beltrano"),
+            ("customer[cpf]", "41810420814"),
+            ("customer[email]", "rufino@email.com"),
+            ("customer[birth_date]", "11/01/2000"),
+            ("customer[contacts][][type_contact]", "M"),
+            ("customer[contacts][][number_contact]", "11976914920"),
+            ("customer[addresses][][type_address]", "D"),
+            ("customer[addresses][][postal_code]", "07097420"),
+            ("customer[addresses][][street]", "Av Bartholomeu de carlos"),
+            ("customer[addresses][][number]", "901"),
+            ("customer[addresses][][completion]", ""),
+            (
+                "customer[addresses][][neighborhood]",
+                "Jardim Flor da Montanha",
+            ),
+            ("customer[addresses][][city]", "Guarulhos"),
+            ("customer[addresses][][state]", "SP"),
+            ("shipping_type", "Sedex"),
+            ("shipping_price", "12.5"),
+        ];
+
+        for (key, value) in expected {
+            assert!(
+                pairs.contains(&(key.to_string(), value.to_string())),
+                "missing {}={}",
+                key,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn t_invalid_shipping_price() {
+        let preferences =
+            CheckoutPreferences::new("order-1".to_string(), vec![sample_product()]).unwrap();
+
+        for price in [-1.0, f64::NAN, f64::INFINITY] {
+            let res = preferences.clone().set_shipping("Sedex", price);
+            assert!(matches!(
+                res,
+                Err(SDKError::InvalidError(InvalidError::ShippingPriceError(_)))
+            ));
+        }
+        assert!(preferences.set_shipping("Retirada", 0.0).is_ok());
+    }
+
+    #[test]
+    fn t_form_payment_terms() {
+        let terms = PaymentTerms::new()
+            .discount(100.0)
+            .additional(5.5)
+            .max_split(10)
+            .interest_free_split(3);
+
+        let form = CheckoutPreferences::new("order-1".to_string(), vec![sample_product()])
+            .unwrap()
+            .set_payment_terms(terms)
+            .unwrap()
+            .to_form("token");
+
+        let pairs = parse_form(&form);
+        let expected = [
+            ("price_discount", "100.00"),
+            ("price_additional", "5.50"),
+            ("max_split_transaction", "10"),
+            ("max_split_no_interest", "3"),
+        ];
+        for (key, value) in expected {
+            assert!(pairs.contains(&(key.to_string(), value.to_string())));
+        }
+    }
+
+    #[test]
+    fn t_payment_terms_over_total() {
+        // 2 * 2453.5 + 10 of shipping
+        let preferences = CheckoutPreferences::new("order-1".to_string(), vec![sample_product()])
+            .unwrap()
+            .set_shipping("Sedex", 10.0)
+            .unwrap();
+
+        let res = preferences
+            .clone()
+            .set_payment_terms(PaymentTerms::new().discount(4917.0));
+        assert!(res.is_err());
+
+        let res = preferences.set_payment_terms(PaymentTerms::new().discount(4916.0));
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn t_checkout_link_token() {
+        let base = YapayEnv::SANDBOX.checkout_link();
+
+        let link = CheckoutLink::from_location(
+            base,
+            "https://tc-intermediador-sandbox.yapay.com.br/payment/transaction/abc123def",
+            "order-1".to_string(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(link.token(), "abc123def");
+        assert_eq!(
+            link.to_string(),
+            "https://tc-intermediador-sandbox.yapay.com.br/payment/transaction/abc123def"
+        );
+
+        let link = CheckoutLink::from_location(
+            base,
+            "https://tc.intermediador.yapay.com.br/payment/transaction?token_transaction=abc123def",
+            "order-1".to_string(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(link.token(), "abc123def");
+
+        let link = CheckoutLink::from_location(
+            base,
+            "/payment/transaction/abc123def",
+            "order-1".to_string(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(link.token(), "abc123def");
+        assert_eq!(
+            link.to_string(),
+            "https://tc-intermediador-sandbox.yapay.com.br/payment/transaction/abc123def"
+        );
+
+        for location in [
+            "https://tc.intermediador.yapay.com.br/payment/transaction?foo=bar",
+            "https://tc.intermediador.yapay.com.br/payment/transaction?tokenizer=abc123def",
+            "/relative/path",
+        ] {
+            let res = CheckoutLink::from_location(base, location, "order-1".to_string(), None);
+            assert!(res.is_err(), "{}", location);
+        }
+    }
+
+    #[test]
+    fn t_expiration_in_the_past() {
+        let res = CheckoutPreferences::new("order-1".to_string(), vec![sample_product()])
+            .unwrap()
+            .set_expiration(OffsetDateTime::now_utc() - time::Duration::hours(1));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn t_invalid_redirect_url() {
+        let res = CheckoutPreferences::new("order-1".to_string(), vec![sample_product()])
+            .unwrap()
+            .set_cancel_url("not an url");
+        assert!(res.is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn t_form_roundtrip(description in "\\PC*", extra in "\\PC*", order_number in "\\PC*") {
+            let mut product = sample_product();
+            product.description = description.clone();
+            product.extra = Some(extra.clone());
+
+            let form = CheckoutPreferences::new(order_number.clone(), vec![product])
+                .unwrap()
+                .to_form("token");
+
+            let pairs = parse_form(&form);
+            prop_assert_eq!(&pairs[1], &("order_number".to_string(), order_number));
+            prop_assert_eq!(
+                &pairs[2],
+                &("transaction_product[][description]".to_string(), description)
+            );
+            prop_assert_eq!(
+                pairs.last().unwrap(),
+                &("transaction_product[][extra]".to_string(), extra)
+            );
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use time::macros::{format_description, offset};
+use time::{OffsetDateTime, PrimitiveDateTime};
+
+use super::CheckoutLink;
+use crate::{InvalidError, SDKError, YapayTransactionStatus, YapayWebhook};
+
+#[derive(Debug, Clone)]
+struct RegisteredLink {
+    expires_at: Option<OffsetDateTime>,
+    /// `token_transaction` of the approved payment that used the link.
+    paid_by: Option<String>,
+}
+
+/// What to do with a notification for an order created through a registered [`CheckoutLink`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckoutVerdict {
+    Accept,
+    /// The order was not created through a registered link.
+    Unknown,
+    /// The transaction happened after the link expired.
+    Expired {
+        expires_at: OffsetDateTime,
+    },
+    /// The link is single-use, and another transaction already paid for it.
+    AlreadyPaid {
+        token_transaction: String,
+    },
+}
+
+/// Keeps track of checkout links, so they work only once and until their expiration.
+///
+/// Yapay's hosted checkout has no expiration, so a customer can still pay on an expired link. The
+/// registry can't prevent that, but it flags the notifications of such payments, so you can
+/// cancel or refund them. Links are single-use: only the first approved transaction of an order
+/// is accepted.
+///
+/// It lives in memory, and is lost on restarts. It holds one entry per registered link, until it
+/// is removed, see [`CheckoutRegistry::remove`] and [`CheckoutRegistry::prune_expired`].
+///
+/// # Usage
+///
+/// ```no_run
+/// use yapay_sdk_rust::{CheckoutRegistry, CheckoutVerdict, YapayWebhook};
+///
+/// fn on_webhook(
+///     registry: &CheckoutRegistry,
+///     webhook: YapayWebhook,
+/// ) -> Result<(), yapay_sdk_rust::errors::SDKError> {
+///     match registry.check_webhook(&webhook)? {
+///         CheckoutVerdict::Accept | CheckoutVerdict::Unknown => { /* fulfill the order */ }
+///         CheckoutVerdict::Expired { .. } | CheckoutVerdict::AlreadyPaid { .. } => {
+///             // refund the transaction
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct CheckoutRegistry {
+    links: Mutex<HashMap<String, RegisteredLink>>,
+}
+
+impl CheckoutRegistry {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts tracking `link`, keyed by its order number.
+    pub fn register(&self, link: &CheckoutLink) {
+        self.links.lock().expect("Poisoned lock.").insert(
+            link.order_number().to_string(),
+            RegisteredLink {
+                expires_at: link.expires_at(),
+                paid_by: None,
+            },
+        );
+    }
+
+    /// Stops tracking the link of `order_number`, e.g. once the order is fulfilled. Returns
+    /// whether it was registered.
+    pub fn remove(&self, order_number: &str) -> bool {
+        self.links
+            .lock()
+            .expect("Poisoned lock.")
+            .remove(order_number)
+            .is_some()
+    }
+
+    /// Stops tracking the links that expired before `before`, and returns how many were removed.
+    ///
+    /// Notifications of a removed link are [`CheckoutVerdict::Unknown`], so keep a margin for
+    /// late payments, e.g. pass a day ago. Links without expiration are kept.
+    pub fn prune_expired(&self, before: OffsetDateTime) -> usize {
+        let mut links = self.links.lock().expect("Poisoned lock.");
+        let registered = links.len();
+        links.retain(|_, link| match link.expires_at {
+            Some(expires_at) => expires_at >= before,
+            None => true,
+        });
+
+        registered - links.len()
+    }
+
+    /// Whether the link of `order_number` expired before `now`.
+    pub fn is_expired(&self, order_number: &str, now: OffsetDateTime) -> bool {
+        self.links
+            .lock()
+            .expect("Poisoned lock.")
+            .get(order_number)
+            .is_some_and(|link| link.expires_at.is_some_and(|exp| now > exp))
+    }
+
+    /// Whether the link of `order_number` was already used by an approved payment.
+    pub fn is_paid(&self, order_number: &str) -> bool {
+        self.links
+            .lock()
+            .expect("Poisoned lock.")
+            .get(order_number)
+            .is_some_and(|link| link.paid_by.is_some())
+    }
+
+    /// Checks `webhook` against the link of its order. An accepted approval uses up the link.
+    ///
+    /// Approvals are checked by their payment date, other statuses by the transaction date. Fails
+    /// when the link expires and that date can't be parsed.
+    pub fn check_webhook(&self, webhook: &YapayWebhook) -> Result<CheckoutVerdict, SDKError> {
+        let transaction = &webhook.transaction;
+        let approved = transaction.status_id == YapayTransactionStatus::Aprovada;
+
+        let mut links = self.links.lock().expect("Poisoned lock.");
+        let link = match links.get_mut(&transaction.order_number) {
+            Some(link) => link,
+            None => return Ok(CheckoutVerdict::Unknown),
+        };
+
+        if let Some(paid_by) = &link.paid_by {
+            if *paid_by != webhook.token_transaction {
+                return Ok(CheckoutVerdict::AlreadyPaid {
+                    token_transaction: paid_by.clone(),
+                });
+            }
+        }
+
+        if let Some(expires_at) = link.expires_at {
+            let happened_at = if approved {
+                &transaction.date_payment
+            } else {
+                &transaction.date_transaction
+            };
+            let happened_at = parse_yapay_datetime(happened_at)?;
+
+            if happened_at > expires_at {
+                tracing::warn!(
+                    "transaction {} of order {} happened after its checkout expired",
+                    webhook.token_transaction,
+                    transaction.order_number
+                );
+                return Ok(CheckoutVerdict::Expired { expires_at });
+            }
+        }
+
+        if approved {
+            link.paid_by = Some(webhook.token_transaction.clone());
+        }
+
+        Ok(CheckoutVerdict::Accept)
+    }
+}
+
+/// Yapay sends dates as `2022-05-24T23:09:21`, on Brasília time.
+fn parse_yapay_datetime(raw: &str) -> Result<OffsetDateTime, InvalidError> {
+    PrimitiveDateTime::parse(
+        raw,
+        format_description!("[year]-[month]-[day]T[hour]:[minute]:[second]"),
+    )
+    .map(|datetime| datetime.assume_offset(offset!(-3)))
+    .map_err(|e| InvalidError::WebhookDateError(format!("{}: {}", raw, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::YapayEnv;
+
+    // paid on 2022-05-24T23:09:21-03:00
+    fn approved_webhook() -> YapayWebhook {
+        let raw = include_str!("../../tests/assets/webhook_notification.txt");
+        YapayWebhook::try_from(Vec::from(raw)).unwrap()
+    }
+
+    fn registry_with_expiration(expires_at: OffsetDateTime) -> CheckoutRegistry {
+        let link = CheckoutLink::from_location(
+            YapayEnv::PRODUCTION.checkout_link(),
+            "https://tc.intermediador.yapay.com.br/payment/transaction/abc123",
+            approved_webhook().transaction.order_number,
+            Some(expires_at),
+        )
+        .unwrap();
+
+        let registry = CheckoutRegistry::new();
+        registry.register(&link);
+        registry
+    }
+
+    #[test]
+    fn t_accepts_before_expiration() {
+        let registry = registry_with_expiration(datetime!(2022-05-25 00:00 -3));
+        assert_eq!(
+            registry.check_webhook(&approved_webhook()).unwrap(),
+            CheckoutVerdict::Accept
+        );
+    }
+
+    #[test]
+    fn t_rejects_after_expiration() {
+        let registry = registry_with_expiration(datetime!(2022-05-24 23:00 -3));
+        assert_eq!(
+            registry.check_webhook(&approved_webhook()).unwrap(),
+            CheckoutVerdict::Expired {
+                expires_at: datetime!(2022-05-24 23:00 -3)
+            }
+        );
+    }
+
+    #[test]
+    fn t_single_use() {
+        let registry = registry_with_expiration(datetime!(2022-05-25 00:00 -3));
+        let webhook = approved_webhook();
+        assert_eq!(
+            registry.check_webhook(&webhook).unwrap(),
+            CheckoutVerdict::Accept
+        );
+
+        // redelivery of the same transaction is still fine
+        assert_eq!(
+            registry.check_webhook(&webhook).unwrap(),
+            CheckoutVerdict::Accept
+        );
+
+        let mut other_payment = approved_webhook();
+        other_payment.token_transaction = "another-transaction".to_string();
+        assert_eq!(
+            registry.check_webhook(&other_payment).unwrap(),
+            CheckoutVerdict::AlreadyPaid {
+                token_transaction: webhook.token_transaction.clone()
+            }
+        );
+        assert!(registry.is_paid(&webhook.transaction.order_number));
+        assert!(!registry.is_expired(
+            &webhook.transaction.order_number,
+            datetime!(2022-05-24 0:00 -3)
+        ));
+    }
+
+    #[test]
+    fn t_invalid_webhook_date() {
+        let registry = registry_with_expiration(datetime!(2022-05-25 00:00 -3));
+        let mut webhook = approved_webhook();
+        webhook.transaction.date_payment = "24/05/2022".to_string();
+
+        assert!(matches!(
+            registry.check_webhook(&webhook),
+            Err(SDKError::InvalidError(InvalidError::WebhookDateError(_)))
+        ));
+        assert!(!registry.is_paid(&webhook.transaction.order_number));
+    }
+
+    #[test]
+    fn t_remove_and_prune() {
+        let registry = registry_with_expiration(datetime!(2022-05-25 00:00 -3));
+        let order_number = approved_webhook().transaction.order_number;
+
+        assert_eq!(registry.prune_expired(datetime!(2022-05-24 00:00 -3)), 0);
+        assert_eq!(registry.prune_expired(datetime!(2022-05-26 00:00 -3)), 1);
+        assert_eq!(
+            registry.check_webhook(&approved_webhook()).unwrap(),
+            CheckoutVerdict::Unknown
+        );
+
+        let registry = registry_with_expiration(datetime!(2022-05-25 00:00 -3));
+        assert!(registry.remove(&order_number));
+        assert!(!registry.remove(&order_number));
+    }
+
+    #[test]
+    fn t_unknown_order() {
+        let registry = CheckoutRegistry::new();
+        assert_eq!(
+            registry.check_webhook(&approved_webhook()).unwrap(),
+            CheckoutVerdict::Unknown
+        );
+    }
+}
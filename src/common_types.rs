@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::fmt::Display;
+use std::fmt::{self, Display};
+use std::net::IpAddr;
 use std::num::NonZeroU8;
 
 use lazy_static::lazy_static;
@@ -11,9 +12,13 @@ use time::macros::format_description;
 use time::{Date, OffsetDateTime};
 use validator::Validate;
 
-use crate::errors::InvalidError;
-use crate::helpers::format_available_payment_method;
-use crate::{CanValidate, SDKError};
+use crate::card::{
+    mask_card_number, normalize_card_number, validate_card, CardExpiry, YapayCardDataBuilder,
+};
+use crate::errors::{CreditCardError, InvalidError};
+use crate::helpers::{format_available_payment_method, REDACTED};
+use crate::secret::SecretString;
+use crate::{CanValidate, SDKError, YapayEnv};
 
 lazy_static! {
     static ref REGEX_BIRTH_DATE: Regex = Regex::new(r"\d{2}/\d{2}/\d{4}$").unwrap();
@@ -24,25 +29,114 @@ lazy_static! {
 /// Enum containing the current transactions status.
 ///
 /// Generally you will match this when receiving webhooks from Yapay.
-#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Debug, strum::Display)]
+///
+/// The lifecycle between statuses is available through
+/// [`YapayTransactionStatus::can_transition`] and [`YapayTransactionStatus::is_final`].
+#[derive(
+    Copy, Clone, Deserialize, Serialize, PartialEq, Eq, Hash, Debug, strum::Display, EnumIter,
+)]
 pub enum YapayTransactionStatus {
     #[serde(rename = "4")]
     AguardandoPagamento,
+    #[serde(rename = "5")]
+    EmProcessamento,
     #[serde(rename = "6")]
     Aprovada,
     #[serde(rename = "7")]
     Cancelada,
-    #[serde(rename = "89")]
-    Reprovada,
 
-    /// The code 24 is for both Chargeback and Contestacao.
-    /// Come on Yapay!
+    /// Em contestação.
     #[serde(rename = "24")]
     Contestacao,
+
+    /// Yapay has no code of its own for chargebacks, they are also reported with 24, so
+    /// `status_id` always deserializes as [`YapayTransactionStatus::Contestacao`]. Use
+    /// [`YapayTransactionStatus::with_name`], or [`crate::YapayWebhook::status`], to tell them
+    /// apart by `status_name`.
+    #[serde(rename = "24", skip_deserializing)]
+    Chargeback,
+
+    /// Em monitoramento, the transaction is under anti-fraud analysis.
+    #[serde(rename = "87")]
+    EmMonitoramento,
+
+    /// Em recuperação, the payment failed and the customer may still pay it.
+    #[serde(rename = "88")]
+    EmRecuperacao,
     #[serde(rename = "89")]
-    Monitoring,
-    // #[serde(rename = "W")]
-    // Chargeback,
+    Reprovada,
+}
+
+impl YapayTransactionStatus {
+    /// The `status_id` Yapay uses for this status.
+    pub const fn code(self) -> u8 {
+        match self {
+            Self::AguardandoPagamento => 4,
+            Self::EmProcessamento => 5,
+            Self::Aprovada => 6,
+            Self::Cancelada => 7,
+            Self::Contestacao | Self::Chargeback => 24,
+            Self::EmMonitoramento => 87,
+            Self::EmRecuperacao => 88,
+            Self::Reprovada => 89,
+        }
+    }
+
+    /// Tells a chargeback from a dispute, which share the same code, by the `status_name` Yapay
+    /// sends along with `status_id`.
+    #[must_use]
+    pub fn with_name(self, status_name: &str) -> Self {
+        match self {
+            Self::Contestacao if status_name.to_lowercase().contains("chargeback") => {
+                Self::Chargeback
+            }
+            status => status,
+        }
+    }
+
+    /// Statuses a transaction can move to from this one.
+    pub const fn next_statuses(self) -> &'static [Self] {
+        match self {
+            Self::AguardandoPagamento => &[
+                Self::EmProcessamento,
+                Self::EmMonitoramento,
+                Self::EmRecuperacao,
+                Self::Aprovada,
+                Self::Reprovada,
+                Self::Cancelada,
+            ],
+            Self::EmProcessamento => &[
+                Self::EmMonitoramento,
+                Self::EmRecuperacao,
+                Self::Aprovada,
+                Self::Reprovada,
+                Self::Cancelada,
+            ],
+            Self::EmMonitoramento => &[Self::Aprovada, Self::Reprovada, Self::Cancelada],
+            Self::EmRecuperacao => &[
+                Self::EmProcessamento,
+                Self::Aprovada,
+                Self::Reprovada,
+                Self::Cancelada,
+            ],
+            Self::Aprovada => &[Self::Contestacao, Self::Chargeback, Self::Cancelada],
+            Self::Contestacao => &[Self::Aprovada, Self::Chargeback, Self::Cancelada],
+            Self::Chargeback => &[Self::Aprovada, Self::Cancelada],
+            Self::Cancelada | Self::Reprovada => &[],
+        }
+    }
+
+    /// Whether a transaction can move from this status to `to`.
+    ///
+    /// Staying on the same status is not a transition, and returns `false`.
+    pub fn can_transition(self, to: Self) -> bool {
+        self.next_statuses().contains(&to)
+    }
+
+    /// A final status never changes again.
+    pub const fn is_final(self) -> bool {
+        self.next_statuses().is_empty()
+    }
 }
 
 #[derive(Validate, Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -88,6 +182,132 @@ impl YapayCustomer {
             Err(e) => Err(InvalidError::ValidatorLibError(e)),
         }
     }
+
+    /// Typed alternative to [`YapayCustomer::new`], with named setters.
+    pub fn builder() -> YapayCustomerBuilder {
+        YapayCustomerBuilder::default()
+    }
+
+    /// Whether the delivery address is the same as the billing one, ignoring case and
+    /// surrounding whitespace. [`None`] when either is missing.
+    ///
+    /// For your own fraud rules, it is not sent to Yapay.
+    pub fn shipping_matches_billing(&self) -> Option<bool> {
+        let find = |kind: AddressType| {
+            self.addresses
+                .iter()
+                .find(|address| address.type_address == kind)
+        };
+        let billing = find(AddressType::Cobranca)?;
+        let shipping = find(AddressType::Entrega)?;
+
+        Some(billing.same_place(shipping))
+    }
+}
+
+/// Builds a [`YapayCustomer`] with named setters. Everything is validated at
+/// [`YapayCustomerBuilder::build`].
+///
+/// # Usage
+///
+/// ```
+/// use time::macros::date;
+/// use yapay_sdk_rust::{CustomerPhoneContact, PhoneContactType, YapayCustomer};
+///
+/// let customer = YapayCustomer::builder()
+///     .name("Rufino Beltrano")
+///     .cpf("418.104.208-14")
+///     .email("rufino@example.com")
+///     .birth_date(date!(1990 - 01 - 31))
+///     .contact(CustomerPhoneContact {
+///         type_contact: PhoneContactType::Celular,
+///         number_contact: "11999999999".to_string(),
+///     })
+///     .build()
+///     .expect("valid customer");
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct YapayCustomerBuilder {
+    name: Option<String>,
+    cpf: Option<String>,
+    cnpj: Option<String>,
+    email: Option<String>,
+    birth_date: Option<Date>,
+    contacts: Vec<CustomerPhoneContact>,
+    addresses: Vec<CustomerAddress>,
+}
+
+impl YapayCustomerBuilder {
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Dots and dashes are removed.
+    #[must_use]
+    pub fn cpf(mut self, cpf: impl AsRef<str>) -> Self {
+        self.cpf = Some(only_digits(cpf.as_ref()));
+        self
+    }
+
+    /// Dots, slashes and dashes are removed.
+    #[must_use]
+    pub fn cnpj(mut self, cnpj: impl AsRef<str>) -> Self {
+        self.cnpj = Some(only_digits(cnpj.as_ref()));
+        self
+    }
+
+    #[must_use]
+    pub fn email(mut self, email: impl Into<String>) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+
+    #[must_use]
+    pub fn birth_date(mut self, birth_date: Date) -> Self {
+        self.birth_date = Some(birth_date);
+        self
+    }
+
+    /// Adds a phone contact, it can be called more than once.
+    #[must_use]
+    pub fn contact(mut self, contact: CustomerPhoneContact) -> Self {
+        self.contacts.push(contact);
+        self
+    }
+
+    /// Adds an address, it can be called more than once.
+    #[must_use]
+    pub fn address(mut self, address: CustomerAddress) -> Self {
+        self.addresses.push(address);
+        self
+    }
+
+    /// Fails when a field is missing, or invalid, e.g. a CPF without 11 digits.
+    pub fn build(self) -> Result<YapayCustomer, InvalidError> {
+        let birth_date = self
+            .birth_date
+            .ok_or(InvalidError::MissingField("birth_date"))?
+            .format(format_description!("[day]/[month]/[year]"))
+            .expect("Valid format.");
+
+        let mut customer = YapayCustomer::new(
+            self.name.ok_or(InvalidError::MissingField("name"))?,
+            self.cpf.ok_or(InvalidError::MissingField("cpf"))?,
+            self.email.ok_or(InvalidError::MissingField("email"))?,
+            birth_date,
+            self.contacts,
+            self.addresses,
+        )?;
+        customer.cnpj = self.cnpj;
+
+        Ok(customer)
+    }
+}
+
+fn only_digits(raw: &str) -> String {
+    raw.chars().filter(char::is_ascii_digit).collect()
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
@@ -121,6 +341,25 @@ pub struct CustomerAddress {
     pub state: String,
 }
 
+impl CustomerAddress {
+    fn same_place(&self, other: &Self) -> bool {
+        let fields = |address: &Self| {
+            [
+                address.postal_code.clone(),
+                address.street.clone(),
+                address.number.clone(),
+                address.completion.clone(),
+                address.neighborhood.clone(),
+                address.city.clone(),
+                address.state.clone(),
+            ]
+            .map(|field| field.trim().to_lowercase())
+        };
+
+        fields(self) == fields(other)
+    }
+}
+
 #[derive(Validate, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct YapayProduct {
     pub code: String,
@@ -149,6 +388,157 @@ impl YapayProduct {
             extra: None,
         }
     }
+
+    /// Unit price times quantity. Unparsable values count as zero.
+    pub fn total(&self) -> f64 {
+        let price_unit = self.price_unit.parse::<f64>().unwrap_or_default();
+        let quantity = self.quantity.parse::<f64>().unwrap_or_default();
+        price_unit * quantity
+    }
+}
+
+/// Maximum number of installments Yapay allows.
+pub const MAX_INSTALLMENTS: u8 = 12;
+
+/// Discount, additional fees and installment limits of a payment.
+///
+/// Used by both [`crate::CheckoutPreferences::set_payment_terms`] and
+/// [`YapayTransaction::set_payment_terms`], and validated against the order total.
+///
+/// # Usage
+///
+/// ```
+/// use yapay_sdk_rust::PaymentTerms;
+///
+/// // R$ 10 off, up to 6 installments, the first 3 without interest
+/// let terms = PaymentTerms::new()
+///     .discount(10.0)
+///     .max_split(6)
+///     .interest_free_split(3);
+///
+/// assert!(terms.validate_for(100.0).is_ok());
+/// assert!(terms.validate_for(5.0).is_err());
+/// ```
+#[derive(Default, Debug, Copy, Clone, PartialEq, Serialize)]
+pub struct PaymentTerms {
+    discount: Option<f64>,
+    additional: Option<f64>,
+    max_split: Option<u8>,
+    interest_free_split: Option<u8>,
+}
+
+impl PaymentTerms {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Amount subtracted from the order total.
+    #[must_use]
+    pub fn discount(mut self, amount: f64) -> Self {
+        self.discount = Some(amount);
+        self
+    }
+
+    /// Amount added to the order total, e.g. a surcharge or handling fee.
+    #[must_use]
+    pub fn additional(mut self, amount: f64) -> Self {
+        self.additional = Some(amount);
+        self
+    }
+
+    /// Maximum number of installments offered, from 1 to 12.
+    #[must_use]
+    pub fn max_split(mut self, installments: u8) -> Self {
+        self.max_split = Some(installments);
+        self
+    }
+
+    /// Number of installments without interest for the customer, up to `max_split`.
+    #[must_use]
+    pub fn interest_free_split(mut self, installments: u8) -> Self {
+        self.interest_free_split = Some(installments);
+        self
+    }
+
+    /// Checks the terms against `order_total`, which is the sum of products and shipping.
+    pub fn validate_for(&self, order_total: f64) -> Result<(), InvalidError> {
+        let discount = self.discount.unwrap_or_default();
+        let additional = self.additional.unwrap_or_default();
+
+        if !discount.is_finite() || !additional.is_finite() {
+            return Err(InvalidError::PaymentTermsError(
+                "discount and additional must be finite".to_string(),
+            ));
+        }
+
+        if discount < 0.0 || additional < 0.0 {
+            return Err(InvalidError::PaymentTermsError(
+                "discount and additional must not be negative".to_string(),
+            ));
+        }
+
+        if discount >= order_total + additional {
+            return Err(InvalidError::PaymentTermsError(format!(
+                "discount of {} must be lower than the order total of {}",
+                discount,
+                order_total + additional
+            )));
+        }
+
+        let max_split = self.max_split.unwrap_or(MAX_INSTALLMENTS);
+        if !(1..=MAX_INSTALLMENTS).contains(&max_split) {
+            return Err(InvalidError::PaymentTermsError(format!(
+                "max split must be between 1 and {}",
+                MAX_INSTALLMENTS
+            )));
+        }
+
+        if let Some(interest_free) = self.interest_free_split {
+            if interest_free == 0 || interest_free > max_split {
+                return Err(InvalidError::PaymentTermsError(format!(
+                    "interest free split must be between 1 and {}",
+                    max_split
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pairs of Yapay field name and value, only for the terms that were set.
+    pub(crate) fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![];
+
+        if let Some(discount) = self.discount {
+            fields.push(("price_discount", format_price(discount)));
+        }
+        if let Some(additional) = self.additional {
+            fields.push(("price_additional", format_price(additional)));
+        }
+        if let Some(max_split) = self.max_split {
+            fields.push(("max_split_transaction", max_split.to_string()));
+        }
+        if let Some(interest_free) = self.interest_free_split {
+            fields.push(("max_split_no_interest", interest_free.to_string()));
+        }
+
+        fields
+    }
+}
+
+/// Money is sent in reais with two decimal places, so float noise like `0.30000000000000004` is
+/// rounded away.
+fn format_price(amount: f64) -> String {
+    format!("{:.2}", amount)
+}
+
+/// Sum of `products` plus the shipping price, which counts as zero when unparsable.
+pub(crate) fn order_total(products: &[YapayProduct], shipping_price: Option<&str>) -> f64 {
+    let shipping = shipping_price
+        .and_then(|price| price.parse::<f64>().ok())
+        .unwrap_or_default();
+    products.iter().map(YapayProduct::total).sum::<f64>() + shipping
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -168,10 +558,20 @@ pub struct YapayTransaction {
     /// `TransactionResponse`. You must ensure that his field is not repeated ever.
     #[validate(length(max = 20))]
     pub order_number: Option<String>,
-    pub customer_ip: String,
+    pub customer_ip: IpAddr,
     pub shipping_type: Option<String>,
     pub shipping_price: Option<String>,
+    /// The payment terms, see [`YapayTransaction::set_payment_terms`]. They are validated
+    /// against the products when the payment request is built.
     pub price_discount: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_additional: Option<String>,
+    /// Maximum number of installments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_split_transaction: Option<String>,
+    /// Installments without interest for the customer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_split_no_interest: Option<String>,
     /// URL in your server to receive IPN (Instant Payment Notification).
     pub url_notification: String,
     pub free: String,
@@ -184,7 +584,7 @@ impl YapayTransaction {
     /// Notification).
     pub fn online_goods(
         order_number: String,
-        customer_ip: String,
+        customer_ip: IpAddr,
         available_payment_methods: Option<String>,
         notification_url: Option<&str>,
     ) -> Result<Self, SDKError> {
@@ -198,6 +598,9 @@ impl YapayTransaction {
             shipping_type: None,
             shipping_price: None,
             price_discount: "".to_string(),
+            price_additional: None,
+            max_split_transaction: None,
+            max_split_no_interest: None,
             url_notification: notification_url.unwrap_or("").to_string(),
             free: "".to_string(),
         };
@@ -208,8 +611,92 @@ impl YapayTransaction {
         Ok(transaction)
     }
 
-    /// A physical product include a shipping address.
-    pub fn physical_goods() {}
+    /// A physical transaction includes a shipping, delivered to the address of the customer with
+    /// [`AddressType::Entrega`].
+    ///
+    /// `notification_url` should be an URL in your server to receive IPN (Instant Payment
+    /// Notification).
+    pub fn physical_goods(
+        order_number: String,
+        customer_ip: IpAddr,
+        shipping_type: &str,
+        shipping_price: f64,
+        available_payment_methods: Option<String>,
+        notification_url: Option<&str>,
+    ) -> Result<Self, SDKError> {
+        if !shipping_price.is_finite() || shipping_price < 0.0 {
+            return Err(InvalidError::ShippingPriceError(shipping_price).into());
+        }
+
+        let mut transaction = Self::online_goods(
+            order_number,
+            customer_ip,
+            available_payment_methods,
+            notification_url,
+        )?;
+        transaction.shipping_type = Some(shipping_type.to_string());
+        transaction.shipping_price = Some(format_price(shipping_price));
+
+        Ok(transaction)
+    }
+
+    /// Applies discount, additional fees and installment limits, replacing the previous ones.
+    ///
+    /// They are validated against the total of products plus shipping when the payment request
+    /// is built, see [`crate::YapaySDK::create_credit_card_payment`].
+    #[must_use]
+    pub fn set_payment_terms(mut self, terms: PaymentTerms) -> Self {
+        self.price_discount = terms.discount.map_or_else(String::new, format_price);
+        self.price_additional = terms.additional.map(format_price);
+        self.max_split_transaction = terms.max_split.map(|split| split.to_string());
+        self.max_split_no_interest = terms.interest_free_split.map(|split| split.to_string());
+
+        self
+    }
+
+    /// The payment terms as they are sent, read back from their fields.
+    fn payment_terms(&self) -> Result<PaymentTerms, InvalidError> {
+        fn parse<T: std::str::FromStr>(field: &str, value: &str) -> Result<T, InvalidError> {
+            value.parse().map_err(|_| {
+                InvalidError::PaymentTermsError(format!("{} is not a number: {}", field, value))
+            })
+        }
+
+        Ok(PaymentTerms {
+            discount: Some(self.price_discount.as_str())
+                .filter(|discount| !discount.is_empty())
+                .map(|discount| parse("price_discount", discount))
+                .transpose()?,
+            additional: self
+                .price_additional
+                .as_deref()
+                .map(|additional| parse("price_additional", additional))
+                .transpose()?,
+            max_split: self
+                .max_split_transaction
+                .as_deref()
+                .map(|split| parse("max_split_transaction", split))
+                .transpose()?,
+            interest_free_split: self
+                .max_split_no_interest
+                .as_deref()
+                .map(|split| parse("max_split_no_interest", split))
+                .transpose()?,
+        })
+    }
+
+    /// Checks the payment terms, if any, against the total of `products` plus shipping.
+    pub(crate) fn validate_payment_terms(
+        &self,
+        products: &[YapayProduct],
+    ) -> Result<(), InvalidError> {
+        let terms = self.payment_terms()?;
+        if terms == PaymentTerms::default() {
+            return Ok(());
+        }
+
+        terms.validate_for(order_total(products, self.shipping_price.as_deref()))
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -229,16 +716,19 @@ pub struct YaypaySavedCardData {
     /// The card token UUID that was return after a payment request.
     ///
     /// Example: a66cf237-3541-45d1-ab9c-a6b6e3f795f5
-    pub card_token: String,
+    pub card_token: SecretString,
 
     #[validate(length(max = 4))]
-    pub card_cvv: String,
+    pub card_cvv: SecretString,
     #[validate(length(min = 1, max = 2))]
     pub split: String,
 }
 
-#[derive(Validate, Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Card fields are [`SecretString`]s, wiped from memory on drop, e.g. once the payment request
+/// was sent.
+#[derive(Validate, Clone, PartialEq, Serialize, Deserialize)]
 #[validate(schema(function = "validate_card_exp"))]
+#[validate(schema(function = "validate_card_number"))]
 pub struct YapayCardData {
     /// Parte do sistema anti-fraude. Obrigatório nos cartões.
     ///
@@ -246,24 +736,29 @@ pub struct YapayCardData {
     /// [Yapay Fingerprint](https://intermediador.dev.yapay.com.br/#/transacao-fingerprint)
     pub finger_print: String,
     pub payment_method_id: PaymentCreditCard,
-    pub card_name: String,
-    pub card_number: String,
+    pub card_name: SecretString,
+    /// Digits only, it must match the brand of `payment_method_id`.
+    pub card_number: SecretString,
 
     /// Month in format of MM.
     #[validate(length(equal = 2))]
-    pub card_expdate_month: String,
+    pub card_expdate_month: SecretString,
 
     /// Year in format of YYYY.
     #[validate(length(equal = 4))]
-    pub card_expdate_year: String,
+    pub card_expdate_year: SecretString,
 
     #[validate(length(max = 4))]
-    pub card_cvv: String,
+    pub card_cvv: SecretString,
     #[validate(length(min = 1, max = 2))]
     pub split: String,
 }
 
 impl YapayCardData {
+    /// Spaces and dashes are removed from `cc_number`. The number is checked with Luhn, and its
+    /// brand must be `cc`.
+    ///
+    /// The expiration is read as in [`CardExpiry::from_parts`], and stored as `MM` and `YYYY`.
     pub fn new(
         cc: PaymentCreditCard,
         cc_owner_name: String,
@@ -273,14 +768,20 @@ impl YapayCardData {
         cc_cvv: String,
         installments: i8,
     ) -> Result<Self, SDKError> {
+        let (cc_exp_mm, cc_exp_yyyy) = match CardExpiry::from_parts(&cc_exp_mm, &cc_exp_yyyy) {
+            Ok(expiry) => (expiry.month().to_string(), expiry.year().to_string()),
+            // left as is, so validation reports it
+            Err(_) => (cc_exp_mm, cc_exp_yyyy),
+        };
+
         let payment = Self {
             finger_print: "".to_string(),
             payment_method_id: cc,
-            card_name: cc_owner_name,
-            card_number: cc_number,
-            card_expdate_month: cc_exp_mm,
-            card_expdate_year: cc_exp_yyyy,
-            card_cvv: cc_cvv,
+            card_name: cc_owner_name.into(),
+            card_number: normalize_card_number(&cc_number).into(),
+            card_expdate_month: cc_exp_mm.into(),
+            card_expdate_year: cc_exp_yyyy.into(),
+            card_cvv: cc_cvv.into(),
             split: installments.to_string(),
         };
 
@@ -290,6 +791,46 @@ impl YapayCardData {
             Ok(payment)
         }
     }
+
+    /// Typed alternative to [`YapayCardData::new`], with named setters.
+    pub fn builder() -> YapayCardDataBuilder {
+        YapayCardDataBuilder::default()
+    }
+
+    pub fn expiration(&self) -> Result<CardExpiry, CreditCardError> {
+        CardExpiry::from_parts(&self.card_expdate_month, &self.card_expdate_year)
+    }
+
+    /// Sets the anti-fraud finger print, as submitted by the payment form, see
+    /// [`crate::finger_print_snippet`]. Payments without it are refused in production.
+    #[must_use]
+    pub fn set_finger_print(mut self, finger_print: impl Into<String>) -> Self {
+        self.finger_print = finger_print.into();
+        self
+    }
+
+    /// Fails in production when the finger print is missing, as executing the payment would.
+    ///
+    /// Lets you reject the payment form right away, before building the payment request.
+    pub fn check_finger_print(&self, yapay_env: YapayEnv) -> Result<(), InvalidError> {
+        crate::check_finger_print(self.finger_print.trim().is_empty(), yapay_env)
+    }
+}
+
+/// The card number is masked, and the CVV hidden.
+impl fmt::Debug for YapayCardData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("YapayCardData")
+            .field("finger_print", &self.finger_print)
+            .field("payment_method_id", &self.payment_method_id)
+            .field("card_name", &&*self.card_name)
+            .field("card_number", &mask_card_number(&self.card_number))
+            .field("card_expdate_month", &&*self.card_expdate_month)
+            .field("card_expdate_year", &&*self.card_expdate_year)
+            .field("card_cvv", &REDACTED)
+            .field("split", &self.split)
+            .finish()
+    }
 }
 
 impl CanValidate for YapayCardData {}
@@ -298,8 +839,8 @@ pub fn validate_card_exp(card_data: &YapayCardData) -> Result<(), validator::Val
     let now = OffsetDateTime::now_utc().date();
     let res = validate_card_expiration(
         now,
-        &*card_data.card_expdate_month,
-        &*card_data.card_expdate_year,
+        &card_data.card_expdate_month,
+        &card_data.card_expdate_year,
     );
 
     match res {
@@ -312,20 +853,31 @@ pub fn validate_card_exp(card_data: &YapayCardData) -> Result<(), validator::Val
     }
 }
 
-/// Month: 2 char
-/// Year: 4 char
+pub fn validate_card_number(card_data: &YapayCardData) -> Result<(), validator::ValidationError> {
+    validate_card(
+        card_data.payment_method_id,
+        &card_data.card_number,
+        &card_data.card_cvv,
+    )
+    .map_err(|err| validator::ValidationError {
+        code: Cow::from("card_number"),
+        message: Some(Cow::from(err.to_string())),
+        params: HashMap::default(),
+    })
+}
+
+/// The card is valid on `time_cmp` through the last day of its expiration month.
+///
+/// Month: 1 or 2 char
+/// Year: 2 or 4 char
 pub fn validate_card_expiration(
     time_cmp: Date,
     exp_month: &str,
     exp_year: &str,
 ) -> Result<(), SDKError> {
-    let card_expiration = Date::parse(
-        &*format!("01-{}-{}", exp_month, exp_year),
-        format_description!("[day]-[month]-[year]"),
-    )
-    .unwrap();
+    let expiry = CardExpiry::from_parts(exp_month, exp_year).map_err(InvalidError::from)?;
 
-    if card_expiration >= time_cmp {
+    if expiry.is_valid_on(time_cmp) {
         Ok(())
     } else {
         Err(InvalidError::CreditCardExpired.into())
@@ -340,6 +892,17 @@ pub enum AddressType {
     Entrega,
 }
 
+impl AddressType {
+    /// The code Yapay uses for this address type, e.g. `"B"`.
+    #[must_use]
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::Cobranca => "B",
+            Self::Entrega => "D",
+        }
+    }
+}
+
 /// Tabela de Contact
 #[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Debug, strum::Display)]
 pub enum PhoneContactType {
@@ -351,14 +914,69 @@ pub enum PhoneContactType {
     Comercial,
 }
 
-/// Tabela de Contact
-#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Debug)]
+impl PhoneContactType {
+    /// The code Yapay uses for this contact type, e.g. `"M"`.
+    #[must_use]
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::Residencial => "H",
+            Self::Celular => "M",
+            Self::Comercial => "W",
+        }
+    }
+}
+
+/// Any payment method, identified by its Yapay `payment_method_id`.
+///
+/// Ids this SDK doesn't support yet, such as Diners or JCB, are kept as
+/// [`PaymentType::Unsupported`].
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Eq, Debug)]
+#[serde(from = "u16", into = "u16")]
 pub enum PaymentType {
     Card(PaymentCreditCard),
-    BankTransfer(PaymentOtherMethods),
+    Other(PaymentOtherMethods),
+    Unsupported(u16),
+}
+
+impl From<u16> for PaymentType {
+    fn from(id: u16) -> Self {
+        let id_str = id.to_string();
+
+        PaymentCreditCard::iter()
+            .find(|method| method.to_string() == id_str)
+            .map(Self::Card)
+            .or_else(|| {
+                PaymentOtherMethods::iter()
+                    .find(|method| method.to_string() == id_str)
+                    .map(Self::Other)
+            })
+            .unwrap_or(Self::Unsupported(id))
+    }
+}
+
+impl From<PaymentCreditCard> for PaymentType {
+    fn from(method: PaymentCreditCard) -> Self {
+        Self::Card(method)
+    }
 }
 
-#[derive(strum::Display, EnumIter, Copy, Clone, Deserialize, Serialize, PartialEq, Debug)]
+impl From<PaymentOtherMethods> for PaymentType {
+    fn from(method: PaymentOtherMethods) -> Self {
+        Self::Other(method)
+    }
+}
+
+impl From<PaymentType> for u16 {
+    fn from(payment_type: PaymentType) -> Self {
+        match payment_type {
+            PaymentType::Card(method) => method.to_string().parse().expect("Numeric id."),
+            PaymentType::Other(method) => method.to_string().parse().expect("Numeric id."),
+            PaymentType::Unsupported(id) => id,
+        }
+    }
+}
+
+#[derive(strum::Display, EnumIter, Copy, Clone, Deserialize, Serialize, PartialEq, Eq, Debug)]
 pub enum PaymentOtherMethods {
     #[serde(rename = "6")]
     #[strum(serialize = "6")]
@@ -376,7 +994,7 @@ pub enum PaymentOtherMethods {
     BankTransferBB,
 }
 
-#[derive(strum::Display, EnumIter, Copy, Clone, Deserialize, Serialize, PartialEq, Debug)]
+#[derive(strum::Display, EnumIter, Copy, Clone, Deserialize, Serialize, PartialEq, Eq, Debug)]
 pub enum PaymentCreditCard {
     #[serde(rename = "3")]
     #[strum(serialize = "3")]
@@ -399,7 +1017,7 @@ pub enum PaymentCreditCard {
 }
 
 /// Methods that takes this trait, you should pass either `OtherMethods` or `CreditCard`.
-pub trait AsPaymentMethod: Display + IntoEnumIterator {
+pub trait AsPaymentMethod: Display + IntoEnumIterator + Copy + Into<PaymentType> {
     fn payment_methods_all() -> String {
         format_available_payment_method(&<Self as IntoEnumIterator>::iter().collect::<Vec<_>>())
     }
@@ -438,12 +1056,19 @@ pub struct ResponseMessage {
 
 #[cfg(test)]
 mod tests {
+    use std::net::IpAddr;
+    use std::num::NonZeroU8;
+
+    use strum::IntoEnumIterator;
     use time::macros::format_description;
     use time::Date;
 
     use crate::common_types::{
-        validate_card_expiration, AsPaymentMethod, PaymentCreditCard, PaymentOtherMethods,
+        validate_card_expiration, AddressType, AsPaymentMethod, CustomerAddress, PaymentCreditCard,
+        PaymentOtherMethods, PaymentTerms, PhoneContactType, YapayCardData, YapayCustomer,
+        YapayProduct, YapayTransaction, YapayTransactionStatus,
     };
+    use crate::errors::{CreditCardError, InvalidError, SDKError};
     use crate::helpers::format_available_payment_method;
 
     #[test]
@@ -464,6 +1089,101 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[test]
+    fn cc_date_end_of_month() {
+        let fmt = format_description!("[year]/[month padding:zero]/[day]");
+
+        let last_day = Date::parse("2022/05/31", &fmt).unwrap();
+        assert!(validate_card_expiration(last_day, "05", "2022").is_ok());
+        assert!(validate_card_expiration(last_day, "5", "22").is_ok());
+
+        let next_month = Date::parse("2022/06/01", &fmt).unwrap();
+        assert!(matches!(
+            validate_card_expiration(next_month, "05", "2022"),
+            Err(SDKError::InvalidError(InvalidError::CreditCardExpired))
+        ));
+
+        assert!(matches!(
+            validate_card_expiration(last_day, "13", "2022"),
+            Err(SDKError::InvalidError(InvalidError::CreditCardError(
+                CreditCardError::InvalidExpiration
+            )))
+        ));
+    }
+
+    #[test]
+    fn t_card_data_expiration() {
+        let card = YapayCardData::new(
+            PaymentCreditCard::MasterCard,
+            "Joaquim Silva".to_string(),
+            "5555666677778884".to_string(),
+            "6".to_string(),
+            "99".to_string(),
+            "123".to_string(),
+            1,
+        )
+        .unwrap();
+
+        assert_eq!(card.card_expdate_month, "06");
+        assert_eq!(card.card_expdate_year, "2099");
+        assert_eq!(card.expiration().unwrap(), "06/2099".parse().unwrap());
+    }
+
+    fn card_data(cc: PaymentCreditCard, number: &str, cvv: &str) -> Option<YapayCardData> {
+        YapayCardData::new(
+            cc,
+            "Joaquim Silva".to_string(),
+            number.to_string(),
+            "12".to_string(),
+            "2099".to_string(),
+            cvv.to_string(),
+            3,
+        )
+        .ok()
+    }
+
+    #[test]
+    fn t_cc_number_validation() {
+        let card = card_data(PaymentCreditCard::MasterCard, "5555 6666-7777 8884", "411").unwrap();
+        assert_eq!(card.card_number, "5555666677778884");
+
+        assert!(card_data(PaymentCreditCard::MasterCard, "3456 8564 1548 7894", "411").is_none());
+        assert!(card_data(PaymentCreditCard::Visa, "5555 6666 7777 8884", "411").is_none());
+        assert!(card_data(PaymentCreditCard::MasterCard, "5555 6666 7777 8884", "4111").is_none());
+    }
+
+    #[test]
+    fn t_cc_finger_print() {
+        let card = card_data(PaymentCreditCard::MasterCard, "5555 6666 7777 8884", "411").unwrap();
+        assert!(card.finger_print.is_empty());
+
+        let card = card.set_finger_print("session-id");
+        assert_eq!(card.finger_print, "session-id");
+    }
+
+    #[test]
+    fn t_cc_debug_redacted() {
+        let card = card_data(PaymentCreditCard::MasterCard, "5555 6666 7777 8884", "411").unwrap();
+        let formatted = format!("{:?} {:#?}", card, card);
+
+        assert!(formatted.contains("555566******8884"));
+        assert!(!formatted.contains("5555666677778884"));
+        assert!(!formatted.contains("411"));
+    }
+
+    #[test]
+    fn t_cc_fields_move_out() {
+        let card = card_data(PaymentCreditCard::MasterCard, "5555 6666 7777 8884", "411").unwrap();
+        let YapayCardData {
+            card_number,
+            card_cvv,
+            ..
+        } = card;
+
+        assert_eq!(card_number, "5555666677778884");
+        assert_eq!(card_cvv, "411");
+    }
+
     #[test]
     fn t_cc_methods() {
         let res = PaymentCreditCard::payment_methods_all();
@@ -478,4 +1198,273 @@ mod tests {
         ]);
         assert_eq!(res, "6,23".to_string());
     }
+
+    #[test]
+    fn t_payment_terms_installments() {
+        assert!(PaymentTerms::new().max_split(12).validate_for(10.0).is_ok());
+        assert!(PaymentTerms::new()
+            .max_split(13)
+            .validate_for(10.0)
+            .is_err());
+        assert!(PaymentTerms::new().max_split(0).validate_for(10.0).is_err());
+        assert!(PaymentTerms::new()
+            .max_split(3)
+            .interest_free_split(4)
+            .validate_for(10.0)
+            .is_err());
+        assert!(PaymentTerms::new()
+            .additional(-1.0)
+            .validate_for(10.0)
+            .is_err());
+    }
+
+    #[test]
+    fn t_payment_terms_not_finite() {
+        for amount in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(PaymentTerms::new()
+                .discount(amount)
+                .validate_for(10.0)
+                .is_err());
+            assert!(PaymentTerms::new()
+                .additional(amount)
+                .validate_for(10.0)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn t_transaction_payment_terms() {
+        let product = YapayProduct::new(
+            "sku".to_string(),
+            "product".to_string(),
+            NonZeroU8::new(2).unwrap(),
+            50.0,
+        );
+        let transaction = YapayTransaction::online_goods(
+            "order".to_string(),
+            IpAddr::from([127, 0, 0, 1]),
+            None,
+            None,
+        )
+        .unwrap()
+        .set_payment_terms(PaymentTerms::new().discount(10.0).max_split(6));
+
+        assert_eq!(transaction.price_discount, "10.00");
+        assert_eq!(transaction.max_split_transaction.as_deref(), Some("6"));
+        assert_eq!(transaction.price_additional, None);
+        assert!(transaction
+            .validate_payment_terms(&[product.clone()])
+            .is_ok());
+
+        let transaction = transaction.set_payment_terms(PaymentTerms::new().discount(100.0));
+        assert!(transaction
+            .validate_payment_terms(&[product.clone()])
+            .is_err());
+
+        // replaced terms don't keep the previous discount
+        let mut transaction = transaction.set_payment_terms(PaymentTerms::new().max_split(3));
+        assert_eq!(transaction.price_discount, "");
+        assert!(transaction
+            .validate_payment_terms(&[product.clone()])
+            .is_ok());
+
+        // terms set on the fields directly are checked too
+        transaction.price_discount = "100.00".to_string();
+        assert!(transaction
+            .validate_payment_terms(&[product.clone()])
+            .is_err());
+        transaction.price_discount = "ten".to_string();
+        assert!(transaction.validate_payment_terms(&[product]).is_err());
+    }
+
+    #[test]
+    fn t_transaction_physical_goods() {
+        let transaction = YapayTransaction::physical_goods(
+            "order".to_string(),
+            IpAddr::from([127, 0, 0, 1]),
+            "Sedex",
+            10.0,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(transaction.shipping_type.as_deref(), Some("Sedex"));
+        assert_eq!(transaction.shipping_price.as_deref(), Some("10.00"));
+
+        for price in [-1.0, f64::NAN] {
+            let res = YapayTransaction::physical_goods(
+                "order".to_string(),
+                IpAddr::from([127, 0, 0, 1]),
+                "Sedex",
+                price,
+                None,
+                None,
+            );
+            assert!(matches!(
+                res,
+                Err(SDKError::InvalidError(InvalidError::ShippingPriceError(_)))
+            ));
+        }
+    }
+
+    #[test]
+    fn t_payment_terms_in_cents() {
+        let terms = PaymentTerms::new().discount(0.1 + 0.2).additional(2.5);
+        assert_eq!(
+            terms.fields(),
+            vec![
+                ("price_discount", "0.30".to_string()),
+                ("price_additional", "2.50".to_string())
+            ]
+        );
+
+        let transaction = YapayTransaction::online_goods(
+            "order".to_string(),
+            IpAddr::from([127, 0, 0, 1]),
+            None,
+            None,
+        )
+        .unwrap()
+        .set_payment_terms(terms);
+        assert_eq!(transaction.price_discount, "0.30");
+        assert_eq!(transaction.price_additional.as_deref(), Some("2.50"));
+    }
+
+    #[test]
+    fn t_transaction_ipv6() {
+        let transaction = YapayTransaction::online_goods(
+            "order".to_string(),
+            "2001:db8::1".parse().unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let json = serde_json::to_value(&transaction).unwrap();
+        assert_eq!(json["customer_ip"], "2001:db8::1");
+    }
+
+    #[test]
+    fn t_customer_builder() {
+        let customer = YapayCustomer::builder()
+            .name("Rufino Beltrano")
+            .cpf("418.104.208-14")
+            .email("rufino@example.com")
+            .birth_date(time::macros::date!(1990 - 01 - 31))
+            .build()
+            .unwrap();
+
+        assert_eq!(customer.cpf, "41810420814");
+        assert_eq!(customer.birth_date, "31/01/1990");
+        assert_eq!(customer.cnpj, None);
+
+        let res = YapayCustomer::builder()
+            .name("Rufino Beltrano")
+            .cpf("111.111.111-11")
+            .email("rufino@example.com")
+            .birth_date(time::macros::date!(1990 - 01 - 31))
+            .build();
+        assert!(res.is_err());
+
+        let res = YapayCustomer::builder()
+            .name("Rufino Beltrano")
+            .cpf("abc")
+            .email("rufino@example.com")
+            .birth_date(time::macros::date!(1990 - 01 - 31))
+            .build();
+        assert!(matches!(res, Err(InvalidError::ValidatorLibError(_))));
+
+        let res = YapayCustomer::builder().name("Rufino Beltrano").build();
+        assert!(matches!(res, Err(InvalidError::MissingField("birth_date"))));
+    }
+
+    #[test]
+    fn t_shipping_matches_billing() {
+        let address = |type_address, street: &str| CustomerAddress {
+            type_address,
+            postal_code: "07097420".to_string(),
+            street: street.to_string(),
+            number: "901".to_string(),
+            completion: String::new(),
+            neighborhood: "Jardim Flor da Montanha".to_string(),
+            city: "Guarulhos".to_string(),
+            state: "SP".to_string(),
+        };
+        let mut customer = YapayCustomer {
+            contacts: vec![],
+            addresses: vec![address(AddressType::Cobranca, "Av Bartholomeu de Carlos")],
+            name: "Rufino Beltrano".to_string(),
+            birth_date: "01/01/1990".to_string(),
+            cpf: "41810420814".to_string(),
+            cnpj: None,
+            email: "rufino@example.com".to_string(),
+        };
+        assert_eq!(customer.shipping_matches_billing(), None);
+
+        customer
+            .addresses
+            .push(address(AddressType::Entrega, " av bartholomeu de carlos "));
+        assert_eq!(customer.shipping_matches_billing(), Some(true));
+
+        customer.addresses[1].number = "902".to_string();
+        assert_eq!(customer.shipping_matches_billing(), Some(false));
+    }
+
+    #[test]
+    fn t_status_codes() {
+        for status in YapayTransactionStatus::iter() {
+            let code = format!("\"{}\"", status.code());
+            assert_eq!(serde_json::to_string(&status).unwrap(), code);
+
+            // chargebacks share the code of disputes
+            let expected = match status {
+                YapayTransactionStatus::Chargeback => YapayTransactionStatus::Contestacao,
+                status => status,
+            };
+            assert_eq!(
+                serde_json::from_str::<YapayTransactionStatus>(&code).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn t_status_chargeback() {
+        use YapayTransactionStatus::*;
+
+        assert_eq!(Contestacao.with_name("Em Contestação"), Contestacao);
+        assert_eq!(Contestacao.with_name("Chargeback"), Chargeback);
+        assert_eq!(Aprovada.with_name("Chargeback"), Aprovada);
+
+        assert!(Aprovada.can_transition(Chargeback));
+        assert!(Contestacao.can_transition(Chargeback));
+        assert!(Chargeback.can_transition(Cancelada));
+    }
+
+    #[test]
+    fn t_status_transitions() {
+        use YapayTransactionStatus::*;
+
+        assert!(AguardandoPagamento.can_transition(Aprovada));
+        assert!(Aprovada.can_transition(Contestacao));
+        assert!(Contestacao.can_transition(Cancelada));
+        assert!(!Aprovada.can_transition(AguardandoPagamento));
+        assert!(!Aprovada.can_transition(Aprovada));
+        assert!(!Cancelada.can_transition(Aprovada));
+
+        assert!(Cancelada.is_final());
+        assert!(Reprovada.is_final());
+        assert!(!Aprovada.is_final());
+    }
+
+    #[test]
+    fn t_contact_and_address_codes() {
+        assert_eq!(PhoneContactType::Celular.to_string(), "Celular");
+        assert_eq!(PhoneContactType::Celular.code(), "M");
+        assert_eq!(AddressType::Entrega.code(), "D");
+        assert_eq!(
+            serde_json::to_string(&AddressType::Cobranca).unwrap(),
+            r#""B""#
+        );
+    }
 }
@@ -1,6 +1,18 @@
+use reqwest::StatusCode;
 use serde::Deserialize;
 use thiserror::Error;
 
+use crate::{PaymentCreditCard, YapayTransactionStatus};
+
+/// Error returned by webhook handlers registered on a `WebhookDispatcher`.
+pub type HandlerError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Error returned by `WebhookStore` implementations.
+pub type StoreError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Error returned by `HttpTransport` implementations.
+pub type TransportError = Box<dyn std::error::Error + Send + Sync>;
+
 #[derive(Error, Debug)]
 pub enum SDKError {
     #[error("{0}")]
@@ -9,20 +21,51 @@ pub enum SDKError {
     #[error(transparent)]
     NetworkError(#[from] reqwest::Error),
 
+    #[error("Transport failed: {0}")]
+    TransportError(#[source] TransportError),
+
     #[error(transparent)]
     InvalidError(#[from] InvalidError),
 
     #[error("{0:?}")]
     PayloadError(ApiError),
 
+    #[error("Checkout failed with status {status}: {body}")]
+    CheckoutError { status: u16, body: String },
+
     #[error("Something wrong happened.")]
     GenericError,
 }
 
+/// Errors from reqwest stay a [`SDKError::NetworkError`], whatever the transport.
+impl From<TransportError> for SDKError {
+    fn from(err: TransportError) -> Self {
+        match err.downcast::<reqwest::Error>() {
+            Ok(err) => Self::NetworkError(*err),
+            Err(err) => Self::TransportError(err),
+        }
+    }
+}
+
 #[derive(Error, Clone, Copy, Debug)]
 pub enum CreditCardError {
     #[error("Card has expired.")]
     Expired,
+
+    #[error("Card number is invalid.")]
+    InvalidNumber,
+
+    #[error("Card number is from {detected:?}, not from {expected:?}.")]
+    BrandMismatch {
+        expected: PaymentCreditCard,
+        detected: Option<PaymentCreditCard>,
+    },
+
+    #[error("Card security code must have {expected_length} digits.")]
+    InvalidCvv { expected_length: usize },
+
+    #[error("Card expiration must be a valid month, as MM/YY or MM/YYYY.")]
+    InvalidExpiration,
 }
 
 #[derive(Error, Debug)]
@@ -30,12 +73,36 @@ pub enum InvalidError {
     #[error("Card has expired.")]
     CreditCardExpired,
 
+    #[error(transparent)]
+    CreditCardError(#[from] CreditCardError),
+
     #[error("Item validation error: {0}")]
     ItemError(String),
 
+    #[error("Payment terms validation error: {0}")]
+    PaymentTermsError(String),
+
     #[error(transparent)]
     URLError(#[from] reqwest::Error),
 
+    #[error("Invalid checkout link: {0}")]
+    CheckoutLinkError(String),
+
+    #[error("Invalid shipping price: {0}")]
+    ShippingPriceError(f64),
+
+    #[error("Checkout expiration must be in the future.")]
+    CheckoutExpirationError,
+
+    #[error("Invalid webhook date: {0}")]
+    WebhookDateError(String),
+
+    #[error("Card payments in production require the anti-fraud finger print.")]
+    FingerPrintMissing,
+
+    #[error("Missing required field: {0}")]
+    MissingField(&'static str),
+
     #[error(transparent)]
     ValidatorLibError(#[from] validator::ValidationErrors),
 }
@@ -53,3 +120,29 @@ pub struct ErrorCause {
     pub description: String,
     pub code: String,
 }
+
+#[derive(Error, Debug)]
+pub enum WebhookError {
+    #[error("Webhook payload is not valid UTF-8.")]
+    Encoding,
+
+    #[error("Failed to parse webhook payload: {0}")]
+    Malformed(#[from] serde_qs::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum DispatchError {
+    #[error("Webhook handler for status {status} failed: {source}")]
+    Handler {
+        status: YapayTransactionStatus,
+        source: HandlerError,
+    },
+}
+
+impl DispatchError {
+    /// The http status to answer Yapay with. It is never 200, so the notification is delivered
+    /// again.
+    pub const fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
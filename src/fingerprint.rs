@@ -0,0 +1,75 @@
+use crate::YapayEnv;
+
+/// Script from Yapay's anti-fraud system, which collects the device data of the customer.
+///
+/// See [Yapay Fingerprint](https://intermediador.dev.yapay.com.br/#/transacao-fingerprint).
+pub const FINGER_PRINT_SCRIPT_URL: &str = "https://static.traycheckout.com.br/js/finger_print.js";
+
+/// Name of the payment form field that carries the finger print.
+pub const FINGER_PRINT_FIELD: &str = "finger_print";
+
+/// The HTML to embed inside the payment form, which fills the finger print of the customer.
+///
+/// The finger print, which is the anti-fraud session id, is generated on the customer's browser
+/// by `window.yapay.FingerPrint({ env }).getFingerPrint()`, from Yapay's script. The snippet
+/// loads the script, and writes the session id into a hidden [`FINGER_PRINT_FIELD`] input, so
+/// it is submitted with the form. Send the submitted value, as is, with the card data through
+/// [`crate::YapayCardData::set_finger_print`]. Card payments without it are refused in
+/// production.
+///
+/// # Usage
+///
+/// ```
+/// use yapay_sdk_rust::{finger_print_snippet, YapayEnv};
+///
+/// let payment_form = format!(
+///     r#"<form method="post" action="/pay">{}<!-- card fields --></form>"#,
+///     finger_print_snippet(YapayEnv::PRODUCTION)
+/// );
+/// ```
+pub fn finger_print_snippet(yapay_env: YapayEnv) -> String {
+    let env = match yapay_env {
+        YapayEnv::PRODUCTION => "production",
+        YapayEnv::SANDBOX => "sandbox",
+    };
+
+    format!(
+        r#"<input type="hidden" id="{field}" name="{field}">
+<script type="text/javascript" src="{url}"></script>
+<script type="text/javascript">
+  document.getElementById("{field}").value =
+    window.yapay.FingerPrint({{ env: "{env}" }}).getFingerPrint();
+</script>"#,
+        field = FINGER_PRINT_FIELD,
+        url = FINGER_PRINT_SCRIPT_URL,
+        env = env
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_finger_print_snippet() {
+        let snippet = finger_print_snippet(YapayEnv::SANDBOX);
+
+        let input = snippet
+            .find(r#"<input type="hidden" id="finger_print" name="finger_print">"#)
+            .unwrap();
+        let script = snippet
+            .find(&format!(r#"src="{}""#, FINGER_PRINT_SCRIPT_URL))
+            .unwrap();
+        let call = snippet
+            .find(r#"document.getElementById("finger_print").value ="#)
+            .unwrap();
+        // the input and the script exist when the session id is written
+        assert!(input < script && script < call);
+        assert!(
+            snippet.contains(r#"window.yapay.FingerPrint({ env: "sandbox" }).getFingerPrint()"#)
+        );
+
+        assert!(finger_print_snippet(YapayEnv::PRODUCTION)
+            .contains(r#"window.yapay.FingerPrint({ env: "production" }).getFingerPrint()"#));
+    }
+}
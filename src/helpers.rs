@@ -4,6 +4,9 @@ use std::collections::HashMap;
 use serde::Serializer;
 use validator::ValidationError;
 
+/// Printed in place of secrets by `Debug` impls.
+pub(crate) const REDACTED: &str = "[REDACTED]";
+
 pub trait Stringify {
     fn stringify(&self) -> Option<String>;
 }
@@ -35,12 +38,9 @@ where
 }
 
 pub fn validate_cpf(cpf: &str) -> Result<(), ValidationError> {
-    let all_digits_repeated = [cpf.chars().next().unwrap()]
-        .repeat(11)
-        .into_iter()
-        .collect::<String>();
+    let all_digits_repeated = cpf.chars().all(|d| cpf.starts_with(d));
 
-    if cpf.len() != 11 || cpf == &*all_digits_repeated {
+    if cpf.len() != 11 || !cpf.chars().all(|d| d.is_ascii_digit()) || all_digits_repeated {
         let error = ValidationError {
             code: Cow::default(),
             message: None,
@@ -89,5 +89,9 @@ mod tests {
     fn t_cpf_invalid() {
         let res = validate_cpf("11111111111");
         assert!(res.is_err());
+
+        for cpf in ["", "418", "4181042081a", "418.104.208"] {
+            assert!(validate_cpf(cpf).is_err());
+        }
     }
 }
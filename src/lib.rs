@@ -30,6 +30,12 @@
 //! # }
 //! ```
 //!
+//! The token can also be loaded with [`YapaySDKBuilder::from_env`] or
+//! [`YapaySDKBuilder::from_file`]. It is kept as an [`AccountToken`], which is never printed.
+//!
+//! Requests are sent with reqwest by default. Another client can be plugged in by implementing
+//! [`HttpTransport`], see [`YapaySDK::with_transport`].
+//!
 //! Once the token is inserted, you can call methods on [`crate::YapaySDK`]
 //!
 //!
@@ -66,13 +72,35 @@
 //!         .expect("Notifications URL failed to validate.")
 //!         .set_available_payment_methods(&PaymentCreditCard::payment_methods_all());
 //!
-//!     let checkout_url = yapay_sdk
+//!     let checkout_link = yapay_sdk
 //!         .create_checkout_page(YapayEnv::PRODUCTION, checkout_preferences)
 //!         .await
 //!         .expect("Something went wrong creating the checkout.");
+//!
+//!     // redirect your customer to `checkout_link.url()`
 //! }
 //! ```
 //!
+//! # Features
+//!
+//! - `native-tls` (default): TLS through the platform library, OpenSSL on Linux.
+//! - `rustls-tls`: TLS through rustls, e.g. for static musl builds. Use it with the default
+//!   features off, adding back `cookies` if needed.
+//! - `cookies` (default): keeps the cookies set by Yapay between requests.
+//! - `blocking`: a synchronous client, see below.
+//! - `actix`, `axum`: webhook extractors for each framework.
+//!
+//! One TLS feature must be enabled, as every Yapay endpoint is https.
+//!
+//! ```toml
+//! yapay-sdk-rust = { version = "0.3", default-features = false, features = ["rustls-tls", "cookies"] }
+//! ```
+//!
+//! # Blocking client
+//!
+//! With the `blocking` feature, [`blocking::YapaySDK`] offers the same methods without async, for
+//! programs that can't run a runtime.
+//!
 //! # Other Examples
 //!
 //! Check out the `tests` folder inside our repository to check for more examples.
@@ -98,40 +126,70 @@
     unused_qualifications
 )]
 
+#[cfg(not(any(feature = "native-tls", feature = "rustls-tls")))]
+compile_error!("Enable a TLS feature of yapay-sdk-rust: `native-tls` or `rustls-tls`.");
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod card;
 mod checkout;
 mod common_types;
 pub mod errors;
+mod fingerprint;
 mod helpers;
+mod secret;
 mod simulation;
 mod transaction;
+mod transport;
 mod webhooks;
 
+use std::fmt;
 use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::Arc;
 
-pub use checkout::CheckoutPreferences;
+pub use card::{luhn_check, CardExpiry, CardMonth, CardYear, Installments, YapayCardDataBuilder};
+pub use checkout::{CheckoutLink, CheckoutPreferences, CheckoutRegistry, CheckoutVerdict};
 use common_types::ResponseRoot;
 pub use common_types::{
     AddressType, AsPaymentMethod, CustomerAddress, CustomerPhoneContact, PaymentCreditCard,
-    PaymentOtherMethods, PhoneContactType, YapayCardData, YapayCustomer, YapayProduct,
-    YapayTransaction, YapayTransactionStatus,
+    PaymentOtherMethods, PaymentTerms, PaymentType, PhoneContactType, YapayCardData, YapayCustomer,
+    YapayCustomerBuilder, YapayProduct, YapayTransaction, YapayTransactionStatus,
 };
-use futures::TryFutureExt;
-use reqwest::header::{CONTENT_TYPE, LOCATION};
-use reqwest::redirect::Policy;
-use reqwest::{Client, Method};
+pub use fingerprint::{finger_print_snippet, FINGER_PRINT_FIELD, FINGER_PRINT_SCRIPT_URL};
+use reqwest::header::{HeaderMap, CONTENT_TYPE, LOCATION};
+use reqwest::{Method, StatusCode};
+use secret::to_zeroizing_json;
+pub use secret::{AccountToken, SecretString};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use validator::Validate;
-pub use webhooks::YapayWebhook;
+#[cfg(feature = "axum")]
+pub use webhooks::{ack_webhook_handler, WebhookRejection};
+pub use webhooks::{
+    InMemoryWebhookStore, WebhookDeduplicator, WebhookDispatcher, WebhookHandler, WebhookStore,
+    WebhookVerdict, YapayWebhook,
+};
+#[cfg(feature = "actix")]
+pub use webhooks::{WebhookExtractConfig, WebhookExtractError};
+use zeroize::Zeroizing;
 
 use crate::errors::{ApiError, InvalidError, SDKError};
-use crate::simulation::{PaymentTaxResponse, SimulatePayload, SimulationResponseWrapper};
+use crate::simulation::SimulatePayload;
+pub use crate::simulation::{
+    InstallmentQuote, PaymentTaxResponse, RateTable, RateTableCache, SimulationResponseWrapper,
+    SplitResponse,
+};
 use crate::transaction::creditcard::TransactionResponse;
 use crate::transaction::{PaymentRequestRoot, TransactionResponseWrapper};
+pub use crate::transport::{HttpRequest, HttpResponse, HttpTransport, ReqwestTransport};
 
 const API_PROD_BASE: &str = "https://api.intermediador.yapay.com.br/api";
 const API_TEST_BASE: &str = "https://api.intermediador.sandbox.yapay.com.br/api";
 
+const PAYMENT_ENDPOINT: &str = "/v3/transactions/payment";
+const SIMULATE_ENDPOINT: &str = "/v1/transactions/simulate_splitting";
+
 const CHECKOUT_PROD_BASE: &str = "https://tc.intermediador.yapay.com.br/payment/transaction";
 const CHECKOUT_TEST_BASE: &str =
     "https://tc-intermediador-sandbox.yapay.com.br/payment/transaction";
@@ -170,122 +228,264 @@ impl YapaySDKBuilder {
     where
         T: ToString,
     {
-        let http_client = Client::builder()
-            .cookie_store(true)
-            .redirect(Policy::none())
-            .build()
-            .expect("Failed to create client.");
+        Self::with_account_token(AccountToken::new(account_token.to_string()))
+    }
 
+    /// Creates an [`YapaySDK`] from an already loaded [`AccountToken`].
+    ///
+    /// Requests are sent through a [`ReqwestTransport`], see [`YapaySDK::with_transport`].
+    pub fn with_account_token(account_token: AccountToken) -> YapaySDK {
         YapaySDK {
-            http_client,
-            account_token: account_token.to_string(),
+            transport: Arc::new(ReqwestTransport::new().expect("Failed to create client.")),
+            account_token,
         }
     }
+
+    /// Creates an [`YapaySDK`] with the token in the environment variable `var`.
+    pub fn from_env(var: &str) -> Result<YapaySDK, SDKError> {
+        AccountToken::from_env(var).map(Self::with_account_token)
+    }
+
+    /// Creates an [`YapaySDK`] with the token in the file at `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<YapaySDK, SDKError> {
+        AccountToken::from_file(path).map(Self::with_account_token)
+    }
 }
 
 #[derive(Debug)]
 pub struct YapaySDK {
-    pub(crate) http_client: Client,
-    pub(crate) account_token: String,
+    pub(crate) transport: Arc<dyn HttpTransport>,
+    pub(crate) account_token: AccountToken,
 }
 
-#[derive(Debug)]
 pub struct SDKJsonRequest<'a, RP> {
-    http_client: &'a Client,
+    transport: &'a dyn HttpTransport,
     method: Method,
     endpoint: &'a str,
-    payload: String,
+    /// Wiped on drop, or after the request is sent.
+    payload: Zeroizing<String>,
+    /// Card payment without the anti-fraud finger print, refused in production.
+    missing_finger_print: bool,
     response_type: PhantomData<RP>,
 }
 
+/// The payload carries the account token, and possibly card data, so only its size is printed.
+impl<'a, RP> fmt::Debug for SDKJsonRequest<'a, RP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SDKJsonRequest")
+            .field("method", &self.method)
+            .field("endpoint", &self.endpoint)
+            .field("payload", &format_args!("{} bytes", self.payload.len()))
+            .finish()
+    }
+}
+
 impl<'a, RP> SDKJsonRequest<'a, RP> {
     #[must_use]
     pub fn from_sdk(sdk: &'a YapaySDK, method: Method, endpoint: &'a str, payload: String) -> Self {
+        Self::from_zeroizing(sdk, method, endpoint, Zeroizing::new(payload))
+    }
+
+    pub(crate) fn from_zeroizing(
+        sdk: &'a YapaySDK,
+        method: Method,
+        endpoint: &'a str,
+        payload: Zeroizing<String>,
+    ) -> Self {
         Self {
-            http_client: &sdk.http_client,
+            transport: &*sdk.transport,
             method,
             endpoint,
             response_type: Default::default(),
             payload,
+            missing_finger_print: false,
         }
     }
 }
 
 impl<'a, RP> SDKJsonRequest<'a, RP> {
     /// Injects bearer token, and return response
-    pub async fn execute(self, yapay_env: YapayEnv) -> Result<RP, SDKError>
+    pub async fn execute(mut self, yapay_env: YapayEnv) -> Result<RP, SDKError>
     where
         RP: DeserializeOwned + Send,
     {
+        check_finger_print(self.missing_finger_print, yapay_env)?;
+
         let api_endpoint = format!("{}{}", yapay_env.api_link(), self.endpoint);
-        tracing::trace!("api endpoint: {:?}", api_endpoint);
-
-        let request = self
-            .http_client
-            .request(self.method, api_endpoint)
-            .body(self.payload)
-            .header(CONTENT_TYPE, "application/json")
-            .build()
-            .unwrap();
-        tracing::trace!("request = {:#?}", request);
-
-        let response = self
-            .http_client
-            .execute(request)
-            .and_then(reqwest::Response::text)
-            .await?;
-        tracing::trace!("response = {}", response);
-
-        // matches errors due to wrong payloads etc
-        let error_jd = serde_json::from_str::<ApiError>(&*response);
-        if let Ok(err) = error_jd {
-            tracing::error!("err = {:#?}", err);
-            return Err(SDKError::PayloadError(err));
-        }
+        tracing::trace!("{} {}", self.method, api_endpoint);
 
-        let jd = &mut serde_json::Deserializer::from_str(&*response);
-        let res: Result<RP, _> = serde_path_to_error::deserialize(jd);
+        let payload = std::mem::take(&mut *self.payload);
+        let request = HttpRequest::new(self.method, api_endpoint, payload)
+            .header(CONTENT_TYPE, "application/json");
 
-        match res {
-            Ok(deserialized_resp) => Ok(deserialized_resp),
-            Err(err) => {
-                tracing::error!("{:?}", err.path());
-                tracing::error!("Error = {:#?}", err);
-                Err(SDKError::GenericError)
-            }
+        let response = self.transport.send(request).await?;
+        tracing::trace!("response with {} bytes", response.body.len());
+
+        parse_json_response(&response.body)
+    }
+}
+
+fn check_finger_print(missing_finger_print: bool, yapay_env: YapayEnv) -> Result<(), InvalidError> {
+    if missing_finger_print && yapay_env == YapayEnv::PRODUCTION {
+        return Err(InvalidError::FingerPrintMissing);
+    }
+    Ok(())
+}
+
+/// Parses an API response body, shared by the async and blocking clients.
+fn parse_json_response<RP>(response: &str) -> Result<RP, SDKError>
+where
+    RP: DeserializeOwned,
+{
+    // matches errors due to wrong payloads etc
+    let error_jd = serde_json::from_str::<ApiError>(response);
+    if let Ok(err) = error_jd {
+        tracing::error!("err = {:#?}", err);
+        return Err(SDKError::PayloadError(err));
+    }
+
+    let jd = &mut serde_json::Deserializer::from_str(response);
+    let res: Result<RP, _> = serde_path_to_error::deserialize(jd);
+
+    match res {
+        Ok(deserialized_resp) => Ok(deserialized_resp),
+        Err(err) => {
+            // the inner error may quote values from the response, so only its kind is logged
+            tracing::error!(
+                "failed to deserialize response at {}: {:?} error at line {} column {}",
+                err.path(),
+                err.inner().classify(),
+                err.inner().line(),
+                err.inner().column()
+            );
+            Err(SDKError::GenericError)
         }
     }
 }
 
+/// The checkout link, when Yapay redirected to it.
+fn checkout_location(status: StatusCode, headers: &HeaderMap) -> Option<String> {
+    if !status.is_redirection() {
+        return None;
+    }
+
+    headers
+        .get(LOCATION)
+        .and_then(|hdr| hdr.to_str().ok())
+        .map(ToString::to_string)
+}
+
+fn checkout_error(status: StatusCode, body: String) -> SDKError {
+    if status.is_success() {
+        tracing::error!("checkout was not redirected, does the http transport follow redirects?");
+    }
+    let status = status.as_u16();
+    tracing::error!("checkout failed with status {}: {}", status, body);
+
+    match serde_json::from_str::<ApiError>(&body) {
+        Ok(err) => SDKError::PayloadError(err),
+        Err(_) => SDKError::CheckoutError { status, body },
+    }
+}
+
+/// The payment payload, and whether it lacks the anti-fraud finger print.
+fn credit_card_payload(
+    account_token: &AccountToken,
+    customer: YapayCustomer,
+    transaction: YapayTransaction,
+    products: Vec<YapayProduct>,
+    cc_payment_data: YapayCardData,
+) -> Result<(Zeroizing<String>, bool), SDKError> {
+    let missing_finger_print = cc_payment_data.finger_print.trim().is_empty();
+    let request_payload = PaymentRequestRoot::new(
+        account_token.clone(),
+        customer,
+        products,
+        transaction,
+        cc_payment_data,
+    )?;
+
+    if let Err(errs) = request_payload.validate() {
+        return Err(InvalidError::ValidatorLibError(errs).into());
+    }
+
+    let payload = to_zeroizing_json(&request_payload).expect("Safe to unwrap.");
+    Ok((payload, missing_finger_print))
+}
+
+fn simulate_payload(account_token: &AccountToken, total_amount: f64) -> Zeroizing<String> {
+    let request_payload = SimulatePayload::new(account_token.clone(), total_amount);
+    to_zeroizing_json(&request_payload).unwrap()
+}
+
+fn restricted_simulate_payload<PM>(
+    account_token: &AccountToken,
+    total_amount: f64,
+    methods: &[PM],
+    max_split: u8,
+) -> Result<Zeroizing<String>, SDKError>
+where
+    PM: AsPaymentMethod,
+{
+    let request_payload = SimulatePayload::new(account_token.clone(), total_amount)
+        .restricted_to(methods, max_split)?;
+    Ok(to_zeroizing_json(&request_payload).unwrap())
+}
+
 pub type CardTransactionResponse = ResponseRoot<TransactionResponseWrapper<TransactionResponse>>;
 pub type SimulationResponse = ResponseRoot<SimulationResponseWrapper<PaymentTaxResponse>>;
 
 impl YapaySDK {
+    /// Sends every request through `transport`, instead of the default [`ReqwestTransport`].
+    ///
+    /// `transport` must not follow redirects, otherwise [`YapaySDK::create_checkout_page`] fails
+    /// with [`SDKError::CheckoutError`], and the status of the page redirected to.
+    #[must_use]
+    pub fn with_transport(mut self, transport: impl HttpTransport + 'static) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// Creates a hosted checkout page, and returns the link your customer should be redirected
+    /// to.
+    ///
+    /// When Yapay refuses the preferences, its error body is returned as
+    /// [`SDKError::PayloadError`] if it could be parsed, or [`SDKError::CheckoutError`] otherwise.
     pub async fn create_checkout_page(
         &self,
         yapay_env: YapayEnv,
         checkout_preferences: CheckoutPreferences,
-    ) -> Result<String, SDKError> {
-        let querystring = checkout_preferences.to_form(&*self.account_token);
-        let request = self
-            .http_client
-            .request(Method::POST, yapay_env.checkout_link())
-            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
-            .body(querystring)
-            .build()
-            .unwrap();
+    ) -> Result<CheckoutLink, SDKError> {
+        let order_number = checkout_preferences.order_number().to_string();
+        let expires_at = checkout_preferences.expires_at();
+
+        let mut querystring = checkout_preferences.to_form(self.account_token.expose());
+        let request = HttpRequest::new(
+            Method::POST,
+            yapay_env.checkout_link().to_string(),
+            std::mem::take(&mut *querystring),
+        )
+        .header(CONTENT_TYPE, "application/x-www-form-urlencoded");
+
+        let response = self.transport.send(request).await?;
 
-        let response = self.http_client.execute(request).await.unwrap();
+        if let Some(location) = checkout_location(response.status, &response.headers) {
+            return CheckoutLink::from_location(
+                yapay_env.checkout_link(),
+                &location,
+                order_number,
+                expires_at,
+            );
+        }
 
-        response
-            .headers()
-            .get(LOCATION)
-            .and_then(|hdr| hdr.to_str().ok())
-            .map(ToString::to_string)
-            .ok_or(SDKError::GenericError)
+        Err(checkout_error(response.status, response.body))
     }
 
     /// Returns an error if it fails to validate any of its arguments.
+    ///
+    /// In production, `cc_payment_data` must carry the anti-fraud finger print, see
+    /// [`YapayCardData::set_finger_print`], otherwise executing the request fails. Check it
+    /// earlier with [`YapayCardData::check_finger_print`].
     pub fn create_credit_card_payment(
         &self,
         customer: YapayCustomer,
@@ -293,38 +493,288 @@ impl YapaySDK {
         products: Vec<YapayProduct>,
         cc_payment_data: YapayCardData,
     ) -> Result<SDKJsonRequest<CardTransactionResponse>, SDKError> {
-        let request_payload = PaymentRequestRoot::new(
-            self.account_token.clone(),
+        let (payload, missing_finger_print) = credit_card_payload(
+            &self.account_token,
             customer,
-            products,
             transaction,
+            products,
             cc_payment_data,
-        );
+        )?;
 
-        if let Err(errs) = request_payload.validate() {
-            return Err(InvalidError::ValidatorLibError(errs).into());
-        }
+        let mut request =
+            SDKJsonRequest::from_zeroizing(self, Method::POST, PAYMENT_ENDPOINT, payload);
+        request.missing_finger_print = missing_finger_print;
 
-        let payload = serde_json::to_string(&request_payload).expect("Safe to unwrap.");
-
-        Ok(SDKJsonRequest::from_sdk(
-            self,
-            Method::POST,
-            "/v3/transactions/payment",
-            payload,
-        ))
+        Ok(request)
     }
 
     #[must_use]
     pub fn simulate_payment(&self, total_amount: f64) -> SDKJsonRequest<SimulationResponse> {
-        let request_payload = SimulatePayload::new(self.account_token.clone(), total_amount);
-        let payload = serde_json::to_string(&request_payload).unwrap();
+        let payload = simulate_payload(&self.account_token, total_amount);
+        SDKJsonRequest::from_zeroizing(self, Method::POST, SIMULATE_ENDPOINT, payload)
+    }
 
-        SDKJsonRequest::from_sdk(
+    /// Simulates `total_amount` only for `methods`, with up to `max_split` installments, e.g. to
+    /// show the same options offered on a checkout.
+    pub fn simulate_payment_for<PM>(
+        &self,
+        total_amount: f64,
+        methods: &[PM],
+        max_split: u8,
+    ) -> Result<SDKJsonRequest<'_, SimulationResponse>, SDKError>
+    where
+        PM: AsPaymentMethod,
+    {
+        let payload =
+            restricted_simulate_payload(&self.account_token, total_amount, methods, max_split)?;
+        Ok(SDKJsonRequest::from_zeroizing(
             self,
             Method::POST,
-            "/v1/transactions/simulate_splitting",
+            SIMULATE_ENDPOINT,
             payload,
-        )
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU8;
+    use std::sync::Mutex;
+
+    use futures::future::BoxFuture;
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+    use crate::errors::TransportError;
+    use crate::helpers::REDACTED;
+
+    /// Records every request, and answers all of them with `response`.
+    #[derive(Debug)]
+    struct RecordingTransport {
+        requests: Arc<Mutex<Vec<(Method, String, HeaderMap, String)>>>,
+        response: Result<HttpResponse, &'static str>,
+    }
+
+    impl RecordingTransport {
+        fn new(response: Result<HttpResponse, &'static str>) -> Self {
+            Self {
+                requests: Arc::default(),
+                response,
+            }
+        }
+    }
+
+    impl HttpTransport for RecordingTransport {
+        fn send(
+            &self,
+            request: HttpRequest,
+        ) -> BoxFuture<'_, Result<HttpResponse, TransportError>> {
+            self.requests.lock().unwrap().push((
+                request.method.clone(),
+                request.url.clone(),
+                request.headers.clone(),
+                String::from_utf8(request.body.to_vec()).unwrap(),
+            ));
+
+            let response = self.response.clone().map_err(TransportError::from);
+            Box::pin(futures::future::ready(response))
+        }
+    }
+
+    fn response(status: StatusCode, headers: HeaderMap, body: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers,
+            body: body.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn t_transport_simulate_payment() {
+        let transport = RecordingTransport::new(Ok(response(
+            StatusCode::OK,
+            HeaderMap::new(),
+            include_str!("../tests/assets/simulate_payment_response.json"),
+        )));
+        let requests = Arc::clone(&transport.requests);
+        let sdk = YapaySDKBuilder::with_token(&"token").with_transport(transport);
+
+        let simulation = sdk
+            .simulate_payment(1000.0)
+            .execute(YapayEnv::SANDBOX)
+            .await
+            .unwrap();
+        assert_eq!(simulation.data_response.payment_methods.len(), 16);
+
+        let requests = requests.lock().unwrap();
+        let (method, url, headers, body) = &requests[0];
+        assert_eq!(*method, Method::POST);
+        assert_eq!(url, &format!("{}{}", API_TEST_BASE, SIMULATE_ENDPOINT));
+        assert_eq!(headers[CONTENT_TYPE], "application/json");
+        assert!(body.contains("\"token_account\":\"token\""));
+    }
+
+    #[tokio::test]
+    async fn t_transport_simulate_payment_for() {
+        let transport = RecordingTransport::new(Ok(response(
+            StatusCode::OK,
+            HeaderMap::new(),
+            include_str!("../tests/assets/simulate_payment_response.json"),
+        )));
+        let requests = Arc::clone(&transport.requests);
+        let sdk = YapaySDKBuilder::with_token(&"token").with_transport(transport);
+
+        sdk.simulate_payment_for(1000.0, &[PaymentCreditCard::Visa], 6)
+            .unwrap()
+            .execute(YapayEnv::SANDBOX)
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        let body = serde_json::from_str::<serde_json::Value>(&requests[0].3).unwrap();
+        assert_eq!(body["available_payment_methods"], "3");
+        assert_eq!(body["max_split_transaction"], "6");
+    }
+
+    #[tokio::test]
+    async fn t_transport_checkout_page() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            LOCATION,
+            HeaderValue::from_static(
+                "https://tc-intermediador-sandbox.yapay.com.br/payment/transaction/abc123def",
+            ),
+        );
+        let transport = RecordingTransport::new(Ok(response(StatusCode::FOUND, headers, "")));
+        let requests = Arc::clone(&transport.requests);
+        let sdk = YapaySDKBuilder::with_token(&"token").with_transport(transport);
+
+        let product = YapayProduct::new(
+            "note-100sk".to_string(),
+            "Notebook".to_string(),
+            NonZeroU8::new(1).unwrap(),
+            2453.5,
+        );
+        let preferences = CheckoutPreferences::new("order-1".to_string(), vec![product]).unwrap();
+
+        let link = sdk
+            .create_checkout_page(YapayEnv::SANDBOX, preferences)
+            .await
+            .unwrap();
+        assert_eq!(link.token(), "abc123def");
+
+        let requests = requests.lock().unwrap();
+        let (_, url, headers, _) = &requests[0];
+        assert_eq!(url, CHECKOUT_TEST_BASE);
+        assert_eq!(headers[CONTENT_TYPE], "application/x-www-form-urlencoded");
+    }
+
+    #[tokio::test]
+    async fn t_transport_followed_redirect() {
+        // a transport following the redirect answers with the checkout page itself
+        let transport = RecordingTransport::new(Ok(response(
+            StatusCode::OK,
+            HeaderMap::new(),
+            "<html>checkout</html>",
+        )));
+        let sdk = YapaySDKBuilder::with_token(&"token").with_transport(transport);
+
+        let product = YapayProduct::new(
+            "note-100sk".to_string(),
+            "Notebook".to_string(),
+            NonZeroU8::new(1).unwrap(),
+            2453.5,
+        );
+        let preferences = CheckoutPreferences::new("order-1".to_string(), vec![product]).unwrap();
+
+        let res = sdk
+            .create_checkout_page(YapayEnv::SANDBOX, preferences)
+            .await;
+        assert!(matches!(
+            res,
+            Err(SDKError::CheckoutError { status: 200, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn t_transport_error() {
+        let sdk = YapaySDKBuilder::with_token(&"token")
+            .with_transport(RecordingTransport::new(Err("connection reset")));
+
+        let res = sdk
+            .simulate_payment(1000.0)
+            .execute(YapayEnv::SANDBOX)
+            .await;
+        assert!(
+            matches!(res, Err(SDKError::TransportError(err)) if err.to_string() == "connection reset")
+        );
+    }
+
+    #[test]
+    fn t_sdk_debug_redacted() {
+        let sdk = YapaySDKBuilder::with_token(&"secret-account-token");
+        let request = sdk.simulate_payment(100.0);
+
+        let formatted = format!("{:?} {:#?}", sdk, request);
+        assert!(formatted.contains(REDACTED));
+        assert!(!formatted.contains("secret-account-token"));
+    }
+
+    #[tokio::test]
+    async fn t_production_requires_finger_print() {
+        let sdk = YapaySDKBuilder::with_token(&"token");
+        let PaymentRequestRoot {
+            customer,
+            items,
+            transaction,
+            mut payment,
+            ..
+        } = serde_json::from_str::<PaymentRequestRoot<YapayCardData>>(include_str!(
+            "../tests/assets/creditcard_request.json"
+        ))
+        .unwrap();
+        payment.card_expdate_year = "2099".into();
+        payment.finger_print = String::new();
+
+        assert!(payment.check_finger_print(YapayEnv::SANDBOX).is_ok());
+        assert!(matches!(
+            payment.check_finger_print(YapayEnv::PRODUCTION),
+            Err(InvalidError::FingerPrintMissing)
+        ));
+
+        let res = sdk
+            .create_credit_card_payment(customer, transaction, items, payment)
+            .unwrap()
+            .execute(YapayEnv::PRODUCTION)
+            .await;
+        assert!(matches!(
+            res,
+            Err(SDKError::InvalidError(InvalidError::FingerPrintMissing))
+        ));
+    }
+
+    #[test]
+    fn t_payment_terms_checked_against_items() {
+        let sdk = YapaySDKBuilder::with_token(&"token");
+        let PaymentRequestRoot {
+            customer,
+            items,
+            transaction,
+            mut payment,
+            ..
+        } = serde_json::from_str::<PaymentRequestRoot<YapayCardData>>(include_str!(
+            "../tests/assets/creditcard_request.json"
+        ))
+        .unwrap();
+        payment.card_expdate_year = "2099".into();
+
+        let total = common_types::order_total(&items, transaction.shipping_price.as_deref());
+        let transaction = transaction.set_payment_terms(PaymentTerms::new().discount(total));
+
+        let res = sdk.create_credit_card_payment(customer, transaction, items, payment);
+        assert!(matches!(
+            res,
+            Err(SDKError::InvalidError(InvalidError::PaymentTermsError(_)))
+        ));
     }
 }
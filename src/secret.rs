@@ -0,0 +1,379 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::ops::Deref;
+use std::path::Path;
+
+use bytes::Bytes;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use subtle::ConstantTimeEq;
+use url::form_urlencoded;
+use validator::HasLen;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::errors::SDKError;
+use crate::helpers::REDACTED;
+
+/// The account token, which authenticates every request to Yapay.
+///
+/// It is wiped from memory on drop, it is never printed by `Debug`, and it is only exposed when a
+/// request payload is serialized.
+///
+/// # Usage
+///
+/// ```no_run
+/// use yapay_sdk_rust::{AccountToken, YapaySDKBuilder};
+///
+/// # fn main() -> Result<(), yapay_sdk_rust::errors::SDKError> {
+/// let token = AccountToken::from_env("YAPAY_ACCOUNT_TOKEN")?;
+/// let yapay_sdk = YapaySDKBuilder::with_account_token(token);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default, Clone)]
+pub struct AccountToken(String);
+
+impl AccountToken {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+
+    /// Reads the token from the environment variable `var`.
+    pub fn from_env(var: &str) -> Result<Self, SDKError> {
+        let token = std::env::var(var).map_err(|err| {
+            SDKError::CredentialsError(format!("could not read {}: {}", var, err))
+        })?;
+
+        Self::non_empty(token)
+    }
+
+    /// Reads the token from the file at `path`, ignoring surrounding whitespace.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SDKError> {
+        let path = path.as_ref();
+        let mut contents = std::fs::read_to_string(path).map_err(|err| {
+            SDKError::CredentialsError(format!("could not read {}: {}", path.display(), err))
+        })?;
+
+        let token = contents.trim().to_string();
+        contents.zeroize();
+
+        Self::non_empty(token)
+    }
+
+    fn non_empty(token: String) -> Result<Self, SDKError> {
+        let token = Self(token);
+        if token.0.is_empty() {
+            return Err(SDKError::CredentialsError(
+                "account token is empty".to_string(),
+            ));
+        }
+        Ok(token)
+    }
+
+    pub(crate) fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for AccountToken {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Compares in constant time, so the comparison does not leak how much of the token matched. The
+/// length can still leak.
+impl PartialEq for AccountToken {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_bytes().ct_eq(other.0.as_bytes()).into()
+    }
+}
+
+impl Eq for AccountToken {}
+
+impl fmt::Debug for AccountToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AccountToken").field(&REDACTED).finish()
+    }
+}
+
+impl Serialize for AccountToken {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for AccountToken {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+/// A string wiped from memory on drop, used for card data.
+///
+/// Unlike a `Drop` on the structs holding it, fields can still be moved out. It derefs to `str`,
+/// and is serialized as a plain string. `Debug` never prints it.
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct SecretString(Zeroizing<String>);
+
+impl SecretString {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self(Zeroizing::new(secret.into()))
+    }
+}
+
+impl Deref for SecretString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        Self::new(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        Self::new(secret)
+    }
+}
+
+impl PartialEq<str> for SecretString {
+    fn eq(&self, other: &str) -> bool {
+        **self == *other
+    }
+}
+
+impl PartialEq<&str> for SecretString {
+    fn eq(&self, other: &&str) -> bool {
+        **self == **other
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Serialize for SecretString {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretString {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// So `#[validate(length(...))]` works on it.
+impl HasLen for &SecretString {
+    fn length(&self) -> u64 {
+        self.chars().count() as u64
+    }
+}
+
+/// Request body that is wiped from memory once the http client is done with it.
+///
+/// Copies made by the client while writing to the socket are out of reach.
+pub(crate) struct ZeroizingBody(Vec<u8>);
+
+impl From<String> for ZeroizingBody {
+    fn from(body: String) -> Self {
+        Self(body.into_bytes())
+    }
+}
+
+impl From<Vec<u8>> for ZeroizingBody {
+    fn from(body: Vec<u8>) -> Self {
+        Self(body)
+    }
+}
+
+impl AsRef<[u8]> for ZeroizingBody {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Drop for ZeroizingBody {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl From<ZeroizingBody> for reqwest::Body {
+    fn from(body: ZeroizingBody) -> Self {
+        Bytes::from_owner(body).into()
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl From<ZeroizingBody> for reqwest::blocking::Body {
+    fn from(body: ZeroizingBody) -> Self {
+        Bytes::from_owner(body).into()
+    }
+}
+
+/// Counts the bytes written to it.
+#[derive(Default)]
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Serializes `value` to JSON into a buffer that is allocated once, with its exact size, so no
+/// partial copy is left behind by reallocations, and that is wiped on drop.
+pub(crate) fn to_zeroizing_json<T>(value: &T) -> serde_json::Result<Zeroizing<String>>
+where
+    T: Serialize + ?Sized,
+{
+    let mut counter = ByteCounter::default();
+    serde_json::to_writer(&mut counter, value)?;
+
+    let mut buffer = Vec::with_capacity(counter.0);
+    serde_json::to_writer(&mut buffer, value)?;
+
+    Ok(Zeroizing::new(
+        String::from_utf8(buffer).expect("serde_json writes valid UTF-8."),
+    ))
+}
+
+/// Encodes `pairs` as `application/x-www-form-urlencoded`, into a buffer that is allocated once,
+/// with its exact size, and that is wiped on drop.
+pub(crate) fn to_zeroizing_form<'a, I>(pairs: I) -> Zeroizing<String>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)> + Clone,
+{
+    let encoded_len = |value: &str| {
+        form_urlencoded::byte_serialize(value.as_bytes())
+            .map(str::len)
+            .sum::<usize>()
+    };
+    let (count, len) = pairs
+        .clone()
+        .into_iter()
+        .fold((0_usize, 0), |(count, len), (key, value)| {
+            (count + 1, len + encoded_len(key) + 1 + encoded_len(value))
+        });
+
+    let mut form = Zeroizing::new(String::with_capacity(len + count.saturating_sub(1)));
+    form_urlencoded::Serializer::new(&mut *form)
+        .extend_pairs(pairs)
+        .finish();
+
+    form
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_token_not_printed() {
+        let token = AccountToken::new("secret-account-token");
+        assert!(!format!("{:?} {:#?}", token, token).contains("secret-account-token"));
+        assert_eq!(
+            serde_json::to_string(&token).unwrap(),
+            "\"secret-account-token\""
+        );
+    }
+
+    #[test]
+    fn t_token_eq() {
+        let token = AccountToken::new("secret-account-token");
+        assert_eq!(token, AccountToken::new("secret-account-token"));
+        assert_ne!(token, AccountToken::new("secret-account-tokem"));
+        assert_ne!(token, AccountToken::new("secret"));
+    }
+
+    #[test]
+    fn t_secret_string() {
+        let secret = SecretString::from("4111111111111111");
+        assert_eq!(secret, "4111111111111111");
+        assert_eq!(format!("{:?}", secret), REDACTED);
+        assert_eq!(
+            serde_json::to_string(&secret).unwrap(),
+            "\"4111111111111111\""
+        );
+
+        let moved: String = secret.to_string();
+        assert_eq!(moved, "4111111111111111");
+    }
+
+    #[test]
+    fn t_token_from_file() {
+        let path = std::env::temp_dir().join(format!("yapay-token-{}", std::process::id()));
+        std::fs::write(&path, "secret-account-token\n").unwrap();
+
+        let token = AccountToken::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(token.expose(), "secret-account-token");
+
+        assert!(AccountToken::from_file(&path).is_err());
+    }
+
+    #[test]
+    fn t_zeroizing_json() {
+        let value = serde_json::json!({
+            "token_account": "secret-account-token",
+            "payment": { "card_number": "4111111111111111", "card_cvv": "123" },
+        });
+
+        let json = to_zeroizing_json(&value).unwrap();
+        assert_eq!(*json, serde_json::to_string(&value).unwrap());
+        assert_eq!(json.capacity(), json.len());
+    }
+
+    #[test]
+    fn t_zeroizing_form() {
+        let pairs = [
+            ("token_account", "secret-account-token"),
+            (
+                "transaction_product[][description]",
+                "Notebook Cinza & Preto",
+            ),
+            ("url_success", "https://shop.com/success?order=1"),
+        ];
+
+        let form = to_zeroizing_form(pairs);
+        assert_eq!(
+            *form,
+            form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(pairs)
+                .finish()
+        );
+        assert_eq!(form.capacity(), form.len());
+        assert!(to_zeroizing_form([]).is_empty());
+    }
+
+    #[test]
+    fn t_token_from_env() {
+        assert!(AccountToken::from_env("YAPAY_SDK_TEST_MISSING_TOKEN").is_err());
+    }
+}
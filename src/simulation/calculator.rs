@@ -0,0 +1,397 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+use super::{PaymentTaxResponse, SimulationResponseWrapper};
+use crate::common_types::PaymentType;
+use crate::errors::SDKError;
+use crate::{YapayEnv, YapaySDK};
+
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Price of a single installment option, as computed by a [`RateTable`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InstallmentQuote {
+    /// Number of installments.
+    pub split: u8,
+    /// Value of each installment.
+    pub value_split: Decimal,
+    /// Total paid by the customer.
+    pub price_customer: Decimal,
+    /// What the seller receives, after fees and retentions.
+    pub price_seller: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SplitRates {
+    split: u8,
+    /// Customer total per unit of the original price.
+    customer: Decimal,
+    /// Seller net per unit of the original price.
+    seller: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct MethodRates {
+    payment_method_id: PaymentType,
+    splits: Vec<SplitRates>,
+}
+
+/// Rates of every payment method and installment, taken from a previous simulation.
+///
+/// Quotes any amount without calling the API. Yapay fees are assumed to be proportional to the
+/// amount, so fixed per transaction fees, like on some boleto plans, are scaled along with it.
+/// Values are rounded to cents, half away from zero.
+///
+/// It is serializable, so it can be persisted between restarts.
+///
+/// # Usage
+///
+/// ```no_run
+/// use rust_decimal::Decimal;
+/// use yapay_sdk_rust::{PaymentCreditCard, RateTable, YapayEnv, YapaySDKBuilder};
+///
+/// # async fn run() -> Result<(), yapay_sdk_rust::errors::SDKError> {
+/// let yapay_sdk = YapaySDKBuilder::with_token(&"YAPAY_ACCOUNT_TOKEN");
+/// let simulation = yapay_sdk
+///     .simulate_payment(1000.0)
+///     .execute(YapayEnv::PRODUCTION)
+///     .await?;
+///
+/// let table = RateTable::from(&simulation.data_response);
+/// for quote in table.quote(PaymentCreditCard::Visa, Decimal::new(24990, 2)) {
+///     println!("{}x of {}", quote.split, quote.value_split);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateTable {
+    methods: Vec<MethodRates>,
+}
+
+impl From<&SimulationResponseWrapper<PaymentTaxResponse>> for RateTable {
+    fn from(simulation: &SimulationResponseWrapper<PaymentTaxResponse>) -> Self {
+        let methods = simulation
+            .payment_methods
+            .iter()
+            .filter(|plan| !plan.price_original.is_zero())
+            .map(|plan| MethodRates {
+                payment_method_id: plan.payment_method_id,
+                splits: plan
+                    .splittings
+                    .iter()
+                    .map(|option| SplitRates {
+                        split: option.split,
+                        customer: option.value_transaction / plan.price_original,
+                        seller: (option.price_seller - option.addition_retention)
+                            / plan.price_original,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self { methods }
+    }
+}
+
+impl RateTable {
+    /// Every payment method in the table.
+    pub fn payment_methods(&self) -> impl Iterator<Item = PaymentType> + '_ {
+        self.methods.iter().map(|method| method.payment_method_id)
+    }
+
+    /// Every installment option of `method` for `amount`. Empty when the method is not in the
+    /// table.
+    pub fn quote(&self, method: impl Into<PaymentType>, amount: Decimal) -> Vec<InstallmentQuote> {
+        let method = method.into();
+        self.methods
+            .iter()
+            .find(|rates| rates.payment_method_id == method)
+            .map(|rates| {
+                rates
+                    .splits
+                    .iter()
+                    .map(|split| split.quote(amount))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// A single installment option of `method` for `amount`.
+    pub fn quote_split(
+        &self,
+        method: impl Into<PaymentType>,
+        split: u8,
+        amount: Decimal,
+    ) -> Option<InstallmentQuote> {
+        self.quote(method, amount)
+            .into_iter()
+            .find(|quote| quote.split == split)
+    }
+}
+
+impl SplitRates {
+    fn quote(&self, amount: Decimal) -> InstallmentQuote {
+        let price_customer = to_cents(amount * self.customer);
+
+        InstallmentQuote {
+            split: self.split,
+            value_split: to_cents(price_customer / Decimal::from(self.split.max(1))),
+            price_customer,
+            price_seller: to_cents(amount * self.seller),
+        }
+    }
+}
+
+fn to_cents(value: Decimal) -> Decimal {
+    value.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+}
+
+#[derive(Debug)]
+struct CachedTable {
+    fetched_at: Instant,
+    table: Arc<RateTable>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    cached: Option<CachedTable>,
+    /// Whether a refresh of a stale table is in flight.
+    refreshing: bool,
+    /// Until when a failed refresh is not retried.
+    retry_after: Option<Instant>,
+}
+
+/// Clears [`CacheState::refreshing`], even when the refresh is cancelled.
+struct RefreshGuard<'a>(&'a Mutex<CacheState>);
+
+impl Drop for RefreshGuard<'_> {
+    fn drop(&mut self) {
+        self.0.lock().expect("Poisoned lock.").refreshing = false;
+    }
+}
+
+/// Keeps a [`RateTable`] in memory, simulating again once it is older than the refresh interval.
+///
+/// The table is fetched by simulating `reference_amount`, on the first call to
+/// [`RateTableCache::get`]. While a stale table is refreshed, or after a refresh fails, the stale
+/// table keeps being served, and a failed refresh is only retried after the retry interval.
+/// Until there is a table, every call fetches one.
+#[derive(Debug)]
+pub struct RateTableCache {
+    sdk: Arc<YapaySDK>,
+    yapay_env: YapayEnv,
+    reference_amount: f64,
+    refresh_interval: Duration,
+    retry_interval: Duration,
+    state: Mutex<CacheState>,
+}
+
+impl RateTableCache {
+    /// Creates an empty cache, refreshed every hour, and retried a minute after a failure.
+    pub fn new(sdk: Arc<YapaySDK>, yapay_env: YapayEnv, reference_amount: f64) -> Self {
+        Self {
+            sdk,
+            yapay_env,
+            reference_amount,
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            retry_interval: DEFAULT_RETRY_INTERVAL,
+            state: Mutex::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn refresh_every(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    /// How long to keep serving a stale table after a failed refresh.
+    #[must_use]
+    pub fn retry_after(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /// Seeds the cache with `table`, e.g. one persisted before a restart.
+    pub fn insert(&self, table: RateTable) {
+        self.state.lock().expect("Poisoned lock.").cached = Some(CachedTable {
+            fetched_at: Instant::now(),
+            table: Arc::new(table),
+        });
+    }
+
+    /// The cached table, refreshed first if it is missing or stale.
+    pub async fn get(&self) -> Result<Arc<RateTable>, SDKError> {
+        let stale = {
+            let mut state = self.state.lock().expect("Poisoned lock.");
+            let backing_off = state
+                .retry_after
+                .is_some_and(|retry_after| Instant::now() < retry_after);
+
+            match &state.cached {
+                Some(current) if current.fetched_at.elapsed() < self.refresh_interval => {
+                    return Ok(Arc::clone(&current.table));
+                }
+                Some(stale) if state.refreshing || backing_off => {
+                    return Ok(Arc::clone(&stale.table));
+                }
+                Some(stale) => {
+                    let stale = Arc::clone(&stale.table);
+                    state.refreshing = true;
+                    Some(stale)
+                }
+                None => None,
+            }
+        };
+
+        let _guard = stale.is_some().then(|| RefreshGuard(&self.state));
+        let fetched = self.fetch().await;
+
+        let mut state = self.state.lock().expect("Poisoned lock.");
+        match (fetched, stale) {
+            (Ok(table), _) => {
+                let table = Arc::new(table);
+                state.cached = Some(CachedTable {
+                    fetched_at: Instant::now(),
+                    table: Arc::clone(&table),
+                });
+                state.retry_after = None;
+                Ok(table)
+            }
+            (Err(err), Some(stale)) => {
+                tracing::warn!("failed to refresh rate table, serving stale one: {}", err);
+                state.retry_after = Some(Instant::now() + self.retry_interval);
+                Ok(stale)
+            }
+            (Err(err), None) => Err(err),
+        }
+    }
+
+    async fn fetch(&self) -> Result<RateTable, SDKError> {
+        let simulation = self
+            .sdk
+            .simulate_payment(self.reference_amount)
+            .execute(self.yapay_env)
+            .await?;
+
+        Ok(RateTable::from(&simulation.data_response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::future::BoxFuture;
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::errors::TransportError;
+    use crate::{
+        HttpRequest, HttpResponse, HttpTransport, PaymentCreditCard, PaymentOtherMethods,
+        SimulationResponse, YapaySDKBuilder,
+    };
+
+    fn table() -> RateTable {
+        let simulation = serde_json::from_str::<SimulationResponse>(include_str!(
+            "../../tests/assets/simulate_payment_response.json"
+        ))
+        .unwrap();
+        RateTable::from(&simulation.data_response)
+    }
+
+    #[test]
+    fn t_quote_reference_amount() {
+        let quotes = table().quote(PaymentCreditCard::Visa, dec!(1000));
+        assert_eq!(quotes.len(), 12);
+
+        assert_eq!(
+            quotes[2],
+            InstallmentQuote {
+                split: 3,
+                value_split: dec!(333.33),
+                price_customer: dec!(1000.00),
+                price_seller: dec!(929.11),
+            }
+        );
+        assert_eq!(quotes[11].value_split, dec!(92.77));
+        assert_eq!(quotes[11].price_customer, dec!(1113.22));
+        assert_eq!(quotes[11].price_seller, dec!(963.10));
+    }
+
+    #[test]
+    fn t_quote_any_amount() {
+        let table = table();
+
+        let quote = table
+            .quote_split(PaymentCreditCard::Visa, 4, dec!(500))
+            .unwrap();
+        assert_eq!(quote.price_customer, dec!(521.30));
+        assert_eq!(quote.value_split, dec!(130.33));
+        assert_eq!(quote.price_seller, dec!(481.55));
+
+        let pix = table
+            .quote_split(PaymentOtherMethods::PIX, 1, dec!(200))
+            .unwrap();
+        assert_eq!(pix.price_seller, dec!(198.10));
+
+        assert!(table
+            .quote_split(PaymentCreditCard::Visa, 13, dec!(500))
+            .is_none());
+    }
+
+    #[derive(Debug, Default)]
+    struct FailingTransport {
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl HttpTransport for FailingTransport {
+        fn send(
+            &self,
+            _request: HttpRequest,
+        ) -> BoxFuture<'_, Result<HttpResponse, TransportError>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Box::pin(futures::future::err("offline".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn t_cache_serves_fresh_table() {
+        let sdk = Arc::new(YapaySDKBuilder::with_token(&"token"));
+        let cache = RateTableCache::new(sdk, YapayEnv::SANDBOX, 1000.0)
+            .refresh_every(Duration::from_secs(60));
+        cache.insert(table());
+
+        // fresh, so no request is made
+        assert_eq!(*cache.get().await.unwrap(), table());
+    }
+
+    #[tokio::test]
+    async fn t_cache_backs_off_after_failure() {
+        let transport = FailingTransport::default();
+        let calls = Arc::clone(&transport.calls);
+        let sdk = YapaySDKBuilder::with_token(&"token").with_transport(transport);
+        let cache = RateTableCache::new(Arc::new(sdk), YapayEnv::SANDBOX, 1000.0)
+            .refresh_every(Duration::ZERO)
+            .retry_after(Duration::from_secs(60));
+        cache.insert(table());
+
+        // stale, the refresh fails, and is not retried yet
+        assert_eq!(*cache.get().await.unwrap(), table());
+        assert_eq!(*cache.get().await.unwrap(), table());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn t_cache_empty_failure() {
+        let sdk = YapaySDKBuilder::with_token(&"token").with_transport(FailingTransport::default());
+        let cache = RateTableCache::new(Arc::new(sdk), YapayEnv::SANDBOX, 1000.0);
+
+        assert!(cache.get().await.is_err());
+    }
+}
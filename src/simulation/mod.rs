@@ -0,0 +1,248 @@
+mod calculator;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+pub use self::calculator::{InstallmentQuote, RateTable, RateTableCache};
+use crate::common_types::{AsPaymentMethod, PaymentType, MAX_INSTALLMENTS};
+use crate::errors::InvalidError;
+use crate::helpers::format_available_payment_method;
+use crate::AccountToken;
+
+/// Wrapper for Transactions endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationResponseWrapper<T> {
+    pub payment_methods: Vec<T>,
+}
+
+impl SimulationResponseWrapper<PaymentTaxResponse> {
+    /// The installment plan of `method`, if it is available for the simulated amount.
+    pub fn plan_for(&self, method: impl Into<PaymentType>) -> Option<&PaymentTaxResponse> {
+        let method = method.into();
+        self.payment_methods
+            .iter()
+            .find(|plan| plan.payment_method_id == method)
+    }
+
+    /// The cheapest option for the customer across every payment method.
+    ///
+    /// On ties, the option with more installments wins, then the method listed first by Yapay.
+    pub fn cheapest_for_customer(&self) -> Option<(&PaymentTaxResponse, &SplitResponse)> {
+        self.payment_methods
+            .iter()
+            .filter_map(|plan| Some((plan, plan.cheapest_for_customer()?)))
+            .reduce(|best, candidate| {
+                if is_cheaper(candidate.1, best.1) {
+                    candidate
+                } else {
+                    best
+                }
+            })
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulatePayload {
+    pub token_account: AccountToken,
+    pub price: String,
+    pub type_response: String,
+    /// Payment methods to simulate, separated by comma. All of the account when empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available_payment_methods: Option<String>,
+    /// Maximum number of installments to simulate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_split_transaction: Option<String>,
+}
+
+impl SimulatePayload {
+    pub fn new(token_account: AccountToken, total_amount: f64) -> Self {
+        Self {
+            token_account,
+            price: total_amount.to_string(),
+            type_response: "J".to_string(),
+            available_payment_methods: None,
+            max_split_transaction: None,
+        }
+    }
+
+    /// Simulates only `methods`, with up to `max_split` installments.
+    pub fn restricted_to<PM>(mut self, methods: &[PM], max_split: u8) -> Result<Self, InvalidError>
+    where
+        PM: AsPaymentMethod,
+    {
+        if methods.is_empty() {
+            return Err(InvalidError::PaymentTermsError(
+                "at least one payment method must be simulated".to_string(),
+            ));
+        }
+        if !(1..=MAX_INSTALLMENTS).contains(&max_split) {
+            return Err(InvalidError::PaymentTermsError(format!(
+                "max split must be between 1 and {}",
+                MAX_INSTALLMENTS
+            )));
+        }
+
+        self.available_payment_methods = Some(format_available_payment_method(methods));
+        self.max_split_transaction = Some(max_split.to_string());
+        Ok(self)
+    }
+}
+
+/// Installment plan of a single payment method.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentTaxResponse {
+    /// Every installment option, from 1 up to `split`.
+    pub splittings: Vec<SplitResponse>,
+    pub price_customer: Decimal,
+    pub price_seller: Decimal,
+    pub price_original: Decimal,
+    /// Max number of installments.
+    pub split: u8,
+    pub payment_method_name: String,
+    pub payment_method_id: PaymentType,
+}
+
+impl PaymentTaxResponse {
+    /// The option with the lowest total for the customer, preferring more installments on ties.
+    pub fn cheapest_for_customer(&self) -> Option<&SplitResponse> {
+        self.splittings.iter().reduce(|best, candidate| {
+            if is_cheaper(candidate, best) {
+                candidate
+            } else {
+                best
+            }
+        })
+    }
+
+    /// Max number of installments without interest for the customer.
+    pub fn max_interest_free_installments(&self) -> Option<u8> {
+        self.splittings
+            .iter()
+            .filter(|option| option.is_interest_free())
+            .map(|option| option.split)
+            .max()
+    }
+}
+
+/// A single installment option.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SplitResponse {
+    /// Number of installments.
+    pub split: u8,
+    /// Value of each installment.
+    pub value_split: Decimal,
+    /// Total paid by the customer.
+    pub value_transaction: Decimal,
+
+    /// Fee retained from the seller when the installments are interest free.
+    pub addition_retention: Decimal,
+
+    /// Percent added per installment.
+    pub split_rate: Decimal,
+
+    /// The base value the seller will receive after the retention fee.
+    pub price_seller: Decimal,
+}
+
+impl SplitResponse {
+    pub fn is_interest_free(&self) -> bool {
+        self.split_rate.is_zero()
+    }
+}
+
+fn is_cheaper(candidate: &SplitResponse, best: &SplitResponse) -> bool {
+    candidate.value_transaction < best.value_transaction
+        || (candidate.value_transaction == best.value_transaction && candidate.split > best.split)
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::{PaymentCreditCard, PaymentOtherMethods, SimulationResponse};
+
+    fn simulation() -> SimulationResponse {
+        serde_json::from_str::<SimulationResponse>(include_str!(
+            "../../tests/assets/simulate_payment_response.json"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn t_simulate_response() {
+        let methods = simulation().data_response;
+        assert_eq!(methods.payment_methods.len(), 16);
+
+        let visa = methods.plan_for(PaymentCreditCard::Visa).unwrap();
+        assert_eq!(visa.split, 12);
+        assert_eq!(visa.price_seller, dec!(903.69));
+        assert_eq!(visa.splittings[0].split_rate, Decimal::ZERO);
+        assert_eq!(visa.splittings[3].split_rate, dec!(1.69));
+        assert_eq!(visa.splittings[3].value_transaction, dec!(1042.60));
+
+        let pix = methods.plan_for(PaymentOtherMethods::PIX).unwrap();
+        assert_eq!(pix.price_seller, dec!(990.50));
+
+        let diners = &methods.payment_methods[2];
+        assert_eq!(diners.payment_method_id, PaymentType::Unsupported(2));
+    }
+
+    #[test]
+    fn t_simulate_helpers() {
+        let methods = simulation().data_response;
+
+        let visa = methods.plan_for(PaymentCreditCard::Visa).unwrap();
+        assert_eq!(visa.max_interest_free_installments(), Some(3));
+        assert_eq!(visa.cheapest_for_customer().unwrap().split, 3);
+
+        let (plan, option) = methods.cheapest_for_customer().unwrap();
+        assert_eq!(
+            plan.payment_method_id,
+            PaymentType::Card(PaymentCreditCard::Visa)
+        );
+        assert_eq!(option.split, 3);
+        assert_eq!(option.value_transaction, dec!(1000.00));
+    }
+
+    #[test]
+    fn t_simulate_payload_restricted() {
+        let payload = SimulatePayload::new(AccountToken::new("token".to_string()), 100.0)
+            .restricted_to(&[PaymentCreditCard::Visa, PaymentCreditCard::MasterCard], 6)
+            .unwrap();
+        let json = serde_json::to_value(&payload).unwrap();
+
+        assert_eq!(json["available_payment_methods"], "3,4");
+        assert_eq!(json["max_split_transaction"], "6");
+
+        let unrestricted = serde_json::to_value(SimulatePayload::new(
+            AccountToken::new("token".to_string()),
+            100.0,
+        ))
+        .unwrap();
+        assert!(unrestricted.get("available_payment_methods").is_none());
+    }
+
+    #[test]
+    fn t_simulate_payload_invalid_restriction() {
+        let payload = SimulatePayload::new(AccountToken::new("token".to_string()), 100.0);
+
+        assert!(payload
+            .clone()
+            .restricted_to(&[PaymentCreditCard::Visa], 13)
+            .is_err());
+        assert!(payload.restricted_to::<PaymentCreditCard>(&[], 6).is_err());
+    }
+
+    #[test]
+    fn t_payment_type_roundtrip() {
+        for id in [3_u16, 6, 2, 27] {
+            let payment_type = PaymentType::from(id);
+            assert_eq!(u16::from(payment_type), id);
+        }
+        assert_eq!(
+            PaymentType::from(6),
+            PaymentType::Other(PaymentOtherMethods::Boleto)
+        );
+    }
+}
@@ -1,13 +1,17 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
 use crate::common_types::{
     CustomerResponse, TransactionTrace, YapayCardData, YapayCustomer, YapayProduct,
     YapayTransaction,
 };
+use crate::helpers::REDACTED;
+use crate::AccountToken;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PaymentCreditCardPayload {
-    pub token_account: String,
+    pub token_account: AccountToken,
     pub customer: YapayCustomer,
     pub transaction_product: Vec<YapayProduct>,
     pub transaction: YapayTransaction,
@@ -27,7 +31,7 @@ pub struct TransactionResponse {
     pub customer: CustomerResponse,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CreditCardPaymentResponse {
     pub price_payment: String,
     pub price_original: String,
@@ -42,6 +46,25 @@ pub struct CreditCardPaymentResponse {
     pub card_token: String,
 }
 
+/// The card token can charge the card again, so it is hidden.
+impl fmt::Debug for CreditCardPaymentResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CreditCardPaymentResponse")
+            .field("price_payment", &self.price_payment)
+            .field("price_original", &self.price_original)
+            .field("payment_response", &self.payment_response)
+            .field("payment_response_code", &self.payment_response_code)
+            .field("url_payment", &self.url_payment)
+            .field("tid", &self.tid)
+            .field("split", &self.split)
+            .field("payment_method_id", &self.payment_method_id)
+            .field("payment_method_name", &self.payment_method_name)
+            .field("linha_digitavel", &self.linha_digitavel)
+            .field("card_token", &REDACTED)
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -55,6 +78,20 @@ mod tests {
         assert!(jd.is_ok());
     }
 
+    #[test]
+    fn t_credit_card_request_redacted() {
+        let request = serde_json::from_str::<PaymentRequestRoot<YapayCardData>>(include_str!(
+            "../../tests/assets/creditcard_request.json"
+        ))
+        .unwrap();
+
+        let formatted = format!("{:?} {:#?}", request, request);
+        for secret in ["SEU_TOKEN_AQUI", "4111111111111111", "644"] {
+            assert!(!formatted.contains(secret), "{} leaked", secret);
+        }
+        assert!(formatted.contains("411111******1111"));
+    }
+
     #[test]
     fn t_credit_card_response() {
         let jd = serde_json::from_str::<CardTransactionResponse>(include_str!(
@@ -62,4 +99,15 @@ mod tests {
         ));
         assert!(jd.is_ok());
     }
+
+    #[test]
+    fn t_credit_card_response_redacted() {
+        let response = serde_json::from_str::<CardTransactionResponse>(include_str!(
+            "../../tests/assets/creditcard_response.json"
+        ))
+        .unwrap();
+
+        let formatted = format!("{:?}", response);
+        assert!(!formatted.contains("a66cf237-3541-45d1-ab9c-a6b6e3f795f5"));
+    }
 }
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use validator::Validate;
 
 use crate::common_types::{TransactionTrace, YapayCustomer, YapayProduct, YapayTransaction};
-use crate::CanValidate;
+use crate::{AccountToken, CanValidate, SDKError};
 
 pub mod creditcard;
 
@@ -18,7 +18,7 @@ pub struct PaymentRequestRoot<T>
 where
     T: CanValidate,
 {
-    pub token_account: String,
+    pub token_account: AccountToken,
     pub customer: YapayCustomer,
     #[serde(rename = "transaction_product")]
     pub items: Vec<YapayProduct>,
@@ -35,20 +35,24 @@ impl<T> PaymentRequestRoot<T>
 where
     T: CanValidate,
 {
+    /// Fails when the payment terms of `transaction` don't fit the total of `items` plus
+    /// shipping.
     pub fn new(
-        token_account: String,
+        token_account: AccountToken,
         customer: YapayCustomer,
         items: Vec<YapayProduct>,
         transaction: YapayTransaction,
         payment: T,
-    ) -> Self {
-        Self {
+    ) -> Result<Self, SDKError> {
+        transaction.validate_payment_terms(&items)?;
+
+        Ok(Self {
             token_account,
             customer,
             items,
             transaction,
             trace: Default::default(),
             payment,
-        }
+        })
     }
 }
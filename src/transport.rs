@@ -0,0 +1,161 @@
+use std::fmt;
+
+use futures::future::BoxFuture;
+use reqwest::header::{HeaderMap, HeaderValue, IntoHeaderName};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Method, StatusCode};
+use zeroize::Zeroizing;
+
+use crate::errors::{SDKError, TransportError};
+use crate::secret::ZeroizingBody;
+
+/// Sends the http requests of a [`crate::YapaySDK`].
+///
+/// [`ReqwestTransport`] is the default. Implement it to send requests through your own client,
+/// e.g. an instrumented one, one on another runtime, or a test double.
+///
+/// Redirects must not be followed, as the checkout link comes in the `Location` header of the
+/// redirect.
+///
+/// # Usage
+///
+/// ```
+/// use futures::future::BoxFuture;
+/// use yapay_sdk_rust::errors::TransportError;
+/// use yapay_sdk_rust::{HttpRequest, HttpResponse, HttpTransport, YapaySDKBuilder};
+///
+/// #[derive(Debug)]
+/// struct Offline;
+///
+/// impl HttpTransport for Offline {
+///     fn send(
+///         &self,
+///         _request: HttpRequest,
+///     ) -> BoxFuture<'_, Result<HttpResponse, TransportError>> {
+///         Box::pin(async { Err("offline".into()) })
+///     }
+/// }
+///
+/// let yapay_sdk = YapaySDKBuilder::with_token(&"YAPAY_ACCOUNT_TOKEN").with_transport(Offline);
+/// ```
+pub trait HttpTransport: fmt::Debug + Send + Sync {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, TransportError>>;
+}
+
+/// A request built by the SDK.
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    /// Carries the account token, and possibly card data. Wiped on drop.
+    pub body: Zeroizing<Vec<u8>>,
+}
+
+impl HttpRequest {
+    pub(crate) fn new(method: Method, url: String, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            method,
+            url,
+            headers: HeaderMap::new(),
+            body: Zeroizing::new(body.into()),
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn header(mut self, key: impl IntoHeaderName, value: &'static str) -> Self {
+        self.headers.insert(key, HeaderValue::from_static(value));
+        self
+    }
+}
+
+/// Only the size of the body is printed.
+impl fmt::Debug for HttpRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpRequest")
+            .field("method", &self.method)
+            .field("url", &self.url)
+            .field("headers", &self.headers)
+            .field("body", &format_args!("{} bytes", self.body.len()))
+            .finish()
+    }
+}
+
+/// The response to an [`HttpRequest`].
+#[derive(Clone)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+/// Only the size of the body is printed.
+impl fmt::Debug for HttpResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .field("body", &format_args!("{} bytes", self.body.len()))
+            .finish()
+    }
+}
+
+/// The default [`HttpTransport`], on top of [`reqwest::Client`].
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl ReqwestTransport {
+    /// A client that does not follow redirects, and keeps cookies with the `cookies` feature.
+    ///
+    /// Fails when the TLS backend can't be initialized.
+    pub fn new() -> Result<Self, SDKError> {
+        let builder = Client::builder().redirect(Policy::none());
+        #[cfg(feature = "cookies")]
+        let builder = builder.cookie_store(true);
+
+        Ok(Self {
+            client: builder.build()?,
+        })
+    }
+}
+
+/// Uses an already configured `client`, which must be built with
+/// `.redirect(reqwest::redirect::Policy::none())`.
+///
+/// Its redirect policy can't be changed afterwards. A client following redirects, like
+/// `Client::new()`, makes [`crate::YapaySDK::create_checkout_page`] fail with
+/// [`SDKError::CheckoutError`], as the checkout link is lost with the `Location` header.
+impl From<Client> for ReqwestTransport {
+    fn from(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    fn send(
+        &self,
+        mut request: HttpRequest,
+    ) -> BoxFuture<'_, Result<HttpResponse, TransportError>> {
+        Box::pin(async move {
+            let body = ZeroizingBody::from(std::mem::take(&mut *request.body));
+            let response = self
+                .client
+                .request(request.method, request.url)
+                .headers(request.headers)
+                .body(body)
+                .send()
+                .await?;
+
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = response.text().await?;
+
+            Ok(HttpResponse {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+}
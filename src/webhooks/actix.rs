@@ -0,0 +1,196 @@
+//! [`actix_web`] integration, enabled with the `actix` feature.
+
+use actix_web::dev::Payload;
+use actix_web::error::PayloadError;
+use actix_web::http::header::CONTENT_LENGTH;
+use actix_web::http::StatusCode;
+use actix_web::web::BytesMut;
+use actix_web::{FromRequest, HttpRequest, HttpResponse, ResponseError};
+use futures::future::LocalBoxFuture;
+use futures::StreamExt;
+use thiserror::Error;
+
+use super::YapayWebhook;
+use crate::errors::{DispatchError, WebhookError};
+
+/// Default body size limit of the [`YapayWebhook`] extractor, 64KiB.
+///
+/// Yapay notifications are usually smaller than 4KiB.
+const DEFAULT_LIMIT: usize = 64 * 1024;
+
+/// Configuration of the [`YapayWebhook`] extractor.
+///
+/// Register it with `App::app_data` to change the accepted body size.
+///
+/// # Usage
+///
+/// ```no_run
+/// use actix_web::App;
+/// use yapay_sdk_rust::WebhookExtractConfig;
+///
+/// let app = App::new().app_data(WebhookExtractConfig::default().limit(16 * 1024));
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct WebhookExtractConfig {
+    limit: usize,
+}
+
+impl WebhookExtractConfig {
+    /// Sets the maximum accepted body size, in bytes.
+    #[must_use]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+impl Default for WebhookExtractConfig {
+    fn default() -> Self {
+        Self {
+            limit: DEFAULT_LIMIT,
+        }
+    }
+}
+
+/// Errors returned by the [`YapayWebhook`] extractor.
+#[derive(Error, Debug)]
+pub enum WebhookExtractError {
+    #[error("Webhook payload is larger than {limit} bytes.")]
+    Overflow { limit: usize },
+
+    #[error(transparent)]
+    Payload(#[from] PayloadError),
+
+    #[error(transparent)]
+    Invalid(#[from] WebhookError),
+}
+
+impl ResponseError for WebhookExtractError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Overflow { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Payload(_) | Self::Invalid(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// Answers with a non-200 status, so Yapay delivers the notification again.
+///
+/// The body is empty, as the handler error may carry internal details. It is logged by
+/// [`crate::WebhookDispatcher::dispatch`].
+impl ResponseError for DispatchError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::new(self.status_code())
+    }
+}
+
+/// Reads the form encoded body Yapay POSTs and parses it into a [`YapayWebhook`].
+impl FromRequest for YapayWebhook {
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let limit = req
+            .app_data::<WebhookExtractConfig>()
+            .copied()
+            .unwrap_or_default()
+            .limit;
+
+        let content_length = req
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|hdr| hdr.to_str().ok())
+            .and_then(|hdr| hdr.parse::<usize>().ok());
+
+        let mut payload = payload.take();
+
+        Box::pin(async move {
+            if content_length.is_some_and(|length| length > limit) {
+                return Err(WebhookExtractError::Overflow { limit }.into());
+            }
+
+            let mut body = BytesMut::new();
+            while let Some(chunk) = payload.next().await {
+                let chunk = chunk.map_err(WebhookExtractError::from)?;
+                if body.len() + chunk.len() > limit {
+                    return Err(WebhookExtractError::Overflow { limit }.into());
+                }
+                body.extend_from_slice(&chunk);
+            }
+
+            Self::try_from(body.to_vec()).map_err(|err| {
+                tracing::error!("rejected yapay webhook: {}", err);
+                WebhookExtractError::from(err).into()
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+    use crate::YapayTransactionStatus;
+
+    const fn notification() -> &'static str {
+        include_str!("../../tests/assets/webhook_notification.txt")
+    }
+
+    async fn extract(req: TestRequest) -> Result<YapayWebhook, actix_web::Error> {
+        let (req, mut payload) = req.to_http_parts();
+        YapayWebhook::from_request(&req, &mut payload).await
+    }
+
+    #[tokio::test]
+    async fn t_extract_webhook() {
+        let req = TestRequest::post().set_payload(notification());
+
+        let webhook = extract(req).await.unwrap();
+        assert_eq!(
+            webhook.transaction.status_id,
+            YapayTransactionStatus::Aprovada
+        );
+    }
+
+    #[tokio::test]
+    async fn t_extract_invalid_webhook() {
+        let req = TestRequest::post().set_payload("token_transaction=abc");
+
+        let err = extract(req).await.unwrap_err();
+        let status = err.as_response_error().status_code();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn t_dispatch_error_hides_source() {
+        let err = DispatchError::Handler {
+            status: YapayTransactionStatus::Aprovada,
+            source: "password authentication failed for user shop".into(),
+        };
+
+        let response = err.error_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = actix_web::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn t_extract_webhook_limit() {
+        let req = TestRequest::post()
+            .app_data(WebhookExtractConfig::default().limit(128))
+            .set_payload(notification());
+
+        let err = extract(req).await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! [`axum`](::axum) integration, enabled with the `axum` feature.
+
+use std::future::Future;
+
+use ::axum::body::{Bytes, HttpBody};
+use ::axum::extract::rejection::BytesRejection;
+use ::axum::extract::FromRequest;
+use ::axum::http::{Request, StatusCode};
+use ::axum::response::{IntoResponse, Response};
+use ::axum::{async_trait, BoxError};
+use futures::future::BoxFuture;
+
+use super::YapayWebhook;
+use crate::errors::{DispatchError, WebhookError};
+
+/// Rejection used when the [`YapayWebhook`] extractor fails.
+///
+/// Both variants answer with http 400.
+#[derive(Debug)]
+pub enum WebhookRejection {
+    /// The request body could not be read.
+    Body(BytesRejection),
+    /// The body was read, but it is not a valid Yapay notification.
+    Payload(WebhookError),
+}
+
+impl IntoResponse for WebhookRejection {
+    fn into_response(self) -> Response {
+        let message = match self {
+            Self::Body(err) => err.to_string(),
+            Self::Payload(err) => err.to_string(),
+        };
+        tracing::error!("rejected yapay webhook: {}", message);
+
+        (StatusCode::BAD_REQUEST, message).into_response()
+    }
+}
+
+/// Answers with a non-200 status, so Yapay delivers the notification again.
+///
+/// The body is empty, as the handler error may carry internal details. It is logged by
+/// [`crate::WebhookDispatcher::dispatch`].
+impl IntoResponse for DispatchError {
+    fn into_response(self) -> Response {
+        self.status_code().into_response()
+    }
+}
+
+/// Reads the form encoded body Yapay POSTs and parses it into a [`YapayWebhook`].
+#[async_trait]
+impl<S, B> FromRequest<S, B> for YapayWebhook
+where
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    S: Send + Sync,
+{
+    type Rejection = WebhookRejection;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(WebhookRejection::Body)?;
+
+        Self::try_from(body.to_vec()).map_err(WebhookRejection::Payload)
+    }
+}
+
+/// Creates an axum handler that runs `callback` with the parsed notification, and then always
+/// acks with http 200, as Yapay requires.
+///
+/// Payloads that fail to parse are rejected with http 400 before `callback` runs.
+///
+/// # Usage
+///
+/// ```no_run
+/// use axum::routing::post;
+/// use axum::Router;
+/// use yapay_sdk_rust::{ack_webhook_handler, YapayWebhook};
+///
+/// let app: Router = Router::new().route(
+///     "/yapay/notifications",
+///     post(ack_webhook_handler(|webhook: YapayWebhook| async move {
+///         eprintln!("status = {}", webhook.transaction.status_id);
+///     })),
+/// );
+/// ```
+pub fn ack_webhook_handler<F, Fut>(
+    callback: F,
+) -> impl Fn(YapayWebhook) -> BoxFuture<'static, StatusCode> + Clone + Send + Sync + 'static
+where
+    F: Fn(YapayWebhook) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future + Send + 'static,
+{
+    move |webhook| {
+        let callback = callback.clone();
+        Box::pin(async move {
+            callback(webhook).await;
+            StatusCode::OK
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::axum::body::Body;
+    use ::axum::http::header::CONTENT_TYPE;
+
+    use super::*;
+    use crate::YapayTransactionStatus;
+
+    fn webhook_request(body: &'static str) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn t_extract_webhook() {
+        let req = webhook_request(include_str!("../../tests/assets/webhook_notification.txt"));
+
+        let webhook = YapayWebhook::from_request(req, &()).await.unwrap();
+        assert_eq!(
+            webhook.transaction.status_id,
+            YapayTransactionStatus::Aprovada
+        );
+    }
+
+    #[tokio::test]
+    async fn t_extract_invalid_webhook() {
+        let req = webhook_request("token_transaction=abc&transaction[order_number]=1");
+
+        let rejection = YapayWebhook::from_request(req, &()).await.unwrap_err();
+        assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn t_dispatch_error_hides_source() {
+        let err = DispatchError::Handler {
+            status: YapayTransactionStatus::Aprovada,
+            source: "password authentication failed for user shop".into(),
+        };
+
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.body().size_hint().exact(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn t_ack_handler() {
+        let req = webhook_request(include_str!("../../tests/assets/webhook_notification.txt"));
+        let webhook = YapayWebhook::from_request(req, &()).await.unwrap();
+
+        let handler = ack_webhook_handler(|_webhook: YapayWebhook| async { Err::<(), ()>(()) });
+        assert_eq!(handler(webhook).await, StatusCode::OK);
+    }
+}
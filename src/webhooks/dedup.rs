@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use futures::future::BoxFuture;
+
+use super::YapayWebhook;
+use crate::errors::StoreError;
+use crate::YapayTransactionStatus;
+
+/// Storage of the last status accepted per transaction, used by [`WebhookDeduplicator`].
+///
+/// Implement it on top of your database or cache to share it between instances; the
+/// [`InMemoryWebhookStore`] is enough for a single process.
+pub trait WebhookStore: Send + Sync {
+    /// Compares `status` with the last status accepted for `token_transaction`, see
+    /// [`WebhookVerdict::after`], and records it when the verdict is [`WebhookVerdict::Accept`].
+    ///
+    /// It must be atomic, e.g. a single conditional update, so concurrent deliveries of the same
+    /// notification are accepted only once.
+    fn check_and_record<'a>(
+        &'a self,
+        token_transaction: &'a str,
+        status: YapayTransactionStatus,
+    ) -> BoxFuture<'a, Result<WebhookVerdict, StoreError>>;
+
+    /// Undoes a [`WebhookStore::check_and_record`] that accepted `status`, if it is still the last
+    /// status of `token_transaction`.
+    fn release<'a>(
+        &'a self,
+        token_transaction: &'a str,
+        status: YapayTransactionStatus,
+    ) -> BoxFuture<'a, Result<(), StoreError>>;
+}
+
+/// A [`WebhookStore`] that lives in memory, and is lost on restarts.
+///
+/// It holds one entry per transaction, until [`InMemoryWebhookStore::prune_final`] drops it.
+#[derive(Debug, Default)]
+pub struct InMemoryWebhookStore {
+    transactions: Mutex<HashMap<String, Vec<YapayTransactionStatus>>>,
+}
+
+impl InMemoryWebhookStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets the transactions on a final status, see [`YapayTransactionStatus::is_final`], and
+    /// returns how many were dropped.
+    ///
+    /// Call it periodically, e.g. daily. A final notification redelivered afterwards is accepted
+    /// again, so don't call it right after accepting one.
+    pub fn prune_final(&self) -> usize {
+        let mut transactions = self.transactions.lock().expect("Poisoned lock.");
+        let stored = transactions.len();
+        transactions.retain(|_, history| !history.last().is_some_and(|status| status.is_final()));
+
+        stored - transactions.len()
+    }
+}
+
+impl WebhookStore for InMemoryWebhookStore {
+    fn check_and_record<'a>(
+        &'a self,
+        token_transaction: &'a str,
+        status: YapayTransactionStatus,
+    ) -> BoxFuture<'a, Result<WebhookVerdict, StoreError>> {
+        let mut transactions = self.transactions.lock().expect("Poisoned lock.");
+        let history = transactions
+            .entry(token_transaction.to_string())
+            .or_default();
+
+        let verdict = WebhookVerdict::after(history.last().copied(), status);
+        if verdict == WebhookVerdict::Accept {
+            history.push(status);
+        }
+
+        Box::pin(futures::future::ok(verdict))
+    }
+
+    fn release<'a>(
+        &'a self,
+        token_transaction: &'a str,
+        status: YapayTransactionStatus,
+    ) -> BoxFuture<'a, Result<(), StoreError>> {
+        let mut transactions = self.transactions.lock().expect("Poisoned lock.");
+        if let Some(history) = transactions.get_mut(token_transaction) {
+            if history.last() == Some(&status) {
+                history.pop();
+            }
+        }
+
+        Box::pin(futures::future::ok(()))
+    }
+}
+
+/// What to do with a received notification.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WebhookVerdict {
+    /// New status for the transaction, process it.
+    Accept,
+    /// Same status as the last one accepted for the transaction.
+    Duplicate,
+    /// The transaction can't move to this status from its current one, e.g. "Aguardando" after
+    /// "Aprovada". See [`YapayTransactionStatus::can_transition`].
+    Regression { current: YapayTransactionStatus },
+}
+
+impl WebhookVerdict {
+    /// Verdict for `status`, when `last` is the last status accepted for the transaction.
+    ///
+    /// Only the last status counts, so a status can be accepted again later, e.g. "Aprovada"
+    /// after a dispute is won.
+    pub fn after(last: Option<YapayTransactionStatus>, status: YapayTransactionStatus) -> Self {
+        match last {
+            Some(current) if current == status => Self::Duplicate,
+            Some(current) if !current.can_transition(status) => Self::Regression { current },
+            _ => Self::Accept,
+        }
+    }
+}
+
+/// Drops notifications Yapay delivers more than once, or out of order.
+///
+/// Notifications are keyed by `token_transaction` and compared with the last accepted
+/// `status_id`. Dropped notifications should still be answered with http 200, otherwise Yapay
+/// keeps sending them.
+///
+/// # Usage
+///
+/// ```
+/// use yapay_sdk_rust::{InMemoryWebhookStore, WebhookDeduplicator, WebhookVerdict, YapayWebhook};
+///
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// # async fn process(_webhook: &YapayWebhook) -> Result<(), Error> {
+/// #     Ok(())
+/// # }
+/// async fn on_webhook(
+///     dedup: &WebhookDeduplicator<InMemoryWebhookStore>,
+///     webhook: YapayWebhook,
+/// ) -> Result<(), Error> {
+///     if dedup.check_and_record(&webhook).await? != WebhookVerdict::Accept {
+///         return Ok(());
+///     }
+///
+///     if let Err(err) = process(&webhook).await {
+///         // so the notification is accepted again when Yapay redelivers it
+///         dedup.release(&webhook).await?;
+///         return Err(err);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct WebhookDeduplicator<S> {
+    store: S,
+}
+
+impl<S> WebhookDeduplicator<S>
+where
+    S: WebhookStore,
+{
+    pub fn new(store: S) -> Self {
+        Self { store }
+    }
+
+    /// Checks `webhook` against the store, recording it when accepted.
+    pub async fn check_and_record(
+        &self,
+        webhook: &YapayWebhook,
+    ) -> Result<WebhookVerdict, StoreError> {
+        let status = webhook.status();
+        let verdict = self
+            .store
+            .check_and_record(&webhook.token_transaction, status)
+            .await?;
+
+        match verdict {
+            WebhookVerdict::Accept => {}
+            WebhookVerdict::Duplicate => tracing::debug!(
+                "duplicated webhook for {} with status {}",
+                webhook.token_transaction,
+                status
+            ),
+            WebhookVerdict::Regression { current } => tracing::warn!(
+                "out of order webhook for {}: {} after {}",
+                webhook.token_transaction,
+                status,
+                current
+            ),
+        }
+        Ok(verdict)
+    }
+
+    /// Forgets an accepted `webhook` whose processing failed, so its redelivery is accepted.
+    pub async fn release(&self, webhook: &YapayWebhook) -> Result<(), StoreError> {
+        self.store
+            .release(&webhook.token_transaction, webhook.status())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn webhook_with_status(status: YapayTransactionStatus) -> YapayWebhook {
+        let raw = include_str!("../../tests/assets/webhook_notification.txt");
+        let mut webhook = YapayWebhook::try_from(Vec::from(raw)).unwrap();
+        webhook.transaction.status_id = status;
+        webhook
+    }
+
+    #[tokio::test]
+    async fn t_drops_duplicates() {
+        let dedup = WebhookDeduplicator::new(InMemoryWebhookStore::new());
+        let webhook = webhook_with_status(YapayTransactionStatus::Aprovada);
+
+        assert_eq!(
+            dedup.check_and_record(&webhook).await.unwrap(),
+            WebhookVerdict::Accept
+        );
+        assert_eq!(
+            dedup.check_and_record(&webhook).await.unwrap(),
+            WebhookVerdict::Duplicate
+        );
+    }
+
+    #[tokio::test]
+    async fn t_rejects_regressions() {
+        let dedup = WebhookDeduplicator::new(InMemoryWebhookStore::new());
+
+        let approved = webhook_with_status(YapayTransactionStatus::Aprovada);
+        dedup.check_and_record(&approved).await.unwrap();
+
+        let waiting = webhook_with_status(YapayTransactionStatus::AguardandoPagamento);
+        assert_eq!(
+            dedup.check_and_record(&waiting).await.unwrap(),
+            WebhookVerdict::Regression {
+                current: YapayTransactionStatus::Aprovada
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn t_accepts_lifecycle() {
+        let dedup = WebhookDeduplicator::new(InMemoryWebhookStore::new());
+
+        for status in [
+            YapayTransactionStatus::AguardandoPagamento,
+            YapayTransactionStatus::Aprovada,
+            YapayTransactionStatus::Contestacao,
+            // dispute won
+            YapayTransactionStatus::Aprovada,
+        ] {
+            let webhook = webhook_with_status(status);
+            assert_eq!(
+                dedup.check_and_record(&webhook).await.unwrap(),
+                WebhookVerdict::Accept
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn t_release_failed_processing() {
+        let dedup = WebhookDeduplicator::new(InMemoryWebhookStore::new());
+        let webhook = webhook_with_status(YapayTransactionStatus::Aprovada);
+
+        dedup.check_and_record(&webhook).await.unwrap();
+        dedup.release(&webhook).await.unwrap();
+        assert_eq!(
+            dedup.check_and_record(&webhook).await.unwrap(),
+            WebhookVerdict::Accept
+        );
+    }
+
+    #[tokio::test]
+    async fn t_prune_final() {
+        let store = InMemoryWebhookStore::new();
+        store
+            .check_and_record("approved", YapayTransactionStatus::Aprovada)
+            .await
+            .unwrap();
+        store
+            .check_and_record("cancelled", YapayTransactionStatus::Cancelada)
+            .await
+            .unwrap();
+
+        assert_eq!(store.prune_final(), 1);
+        assert_eq!(
+            store
+                .check_and_record("approved", YapayTransactionStatus::Aprovada)
+                .await
+                .unwrap(),
+            WebhookVerdict::Duplicate
+        );
+        assert_eq!(
+            store
+                .check_and_record("cancelled", YapayTransactionStatus::Cancelada)
+                .await
+                .unwrap(),
+            WebhookVerdict::Accept
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn t_concurrent_deliveries_accepted_once() {
+        let dedup = Arc::new(WebhookDeduplicator::new(InMemoryWebhookStore::new()));
+        let webhook = Arc::new(webhook_with_status(YapayTransactionStatus::Aprovada));
+
+        let deliveries = (0..8).map(|_| {
+            let dedup = Arc::clone(&dedup);
+            let webhook = Arc::clone(&webhook);
+            tokio::spawn(async move { dedup.check_and_record(&webhook).await.unwrap() })
+        });
+
+        let verdicts = futures::future::join_all(deliveries).await;
+        let accepted = verdicts
+            .into_iter()
+            .filter(|verdict| *verdict.as_ref().unwrap() == WebhookVerdict::Accept)
+            .count();
+        assert_eq!(accepted, 1);
+    }
+}
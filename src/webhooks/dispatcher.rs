@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+
+use futures::future::BoxFuture;
+use reqwest::StatusCode;
+
+use super::YapayWebhook;
+use crate::errors::{DispatchError, HandlerError};
+use crate::YapayTransactionStatus;
+
+type HandlerFuture = BoxFuture<'static, Result<(), HandlerError>>;
+type BoxedHandler = Box<dyn Fn(YapayWebhook) -> HandlerFuture + Send + Sync>;
+
+/// An async handler registered on a [`WebhookDispatcher`].
+///
+/// Implemented for every `Fn(YapayWebhook) -> impl Future<Output = Result<(), E>>`, where `E`
+/// converts into a [`HandlerError`].
+pub trait WebhookHandler<Fut, E>: Fn(YapayWebhook) -> Fut + Send + Sync + 'static {
+    /// Runs the handler, converting its error.
+    fn handle(&self, webhook: YapayWebhook) -> HandlerFuture;
+}
+
+impl<F, Fut, E> WebhookHandler<Fut, E> for F
+where
+    F: Fn(YapayWebhook) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: Into<HandlerError>,
+{
+    fn handle(&self, webhook: YapayWebhook) -> HandlerFuture {
+        let fut = self(webhook);
+        Box::pin(async move { fut.await.map_err(Into::into) })
+    }
+}
+
+/// Routes each [`YapayWebhook`] to the async handler registered for its
+/// [`YapayTransactionStatus`], as returned by [`YapayWebhook::status`].
+///
+/// Statuses without a handler go to the [`fallback`](WebhookDispatcher::fallback) handler, if
+/// there is one, or are just acknowledged.
+///
+/// A handler failure is returned as a [`DispatchError`], which always maps to a non-200 response,
+/// so Yapay delivers the notification again later.
+///
+/// # Usage
+///
+/// ```
+/// use yapay_sdk_rust::{WebhookDispatcher, YapayWebhook};
+///
+/// let dispatcher = WebhookDispatcher::new()
+///     .on_approved(|webhook: YapayWebhook| async move {
+///         eprintln!("order {} approved", webhook.transaction.order_number);
+///         Ok::<_, std::io::Error>(())
+///     })
+///     .fallback(|webhook: YapayWebhook| async move {
+///         eprintln!("unhandled status {}", webhook.transaction.status_id);
+///         Ok::<_, std::io::Error>(())
+///     });
+///
+/// // later, inside your webhook route:
+/// // let http_status = dispatcher.dispatch_status(webhook).await;
+/// ```
+#[derive(Default)]
+pub struct WebhookDispatcher {
+    handlers: HashMap<YapayTransactionStatus, BoxedHandler>,
+    fallback: Option<BoxedHandler>,
+}
+
+impl Debug for WebhookDispatcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookDispatcher")
+            .field("handlers", &self.handlers.keys().collect::<Vec<_>>())
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+fn boxed_handler<F, Fut, E>(handler: F) -> BoxedHandler
+where
+    F: WebhookHandler<Fut, E>,
+{
+    Box::new(move |webhook| handler.handle(webhook))
+}
+
+impl WebhookDispatcher {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `handler` for notifications with `status`, replacing any previous one.
+    #[must_use]
+    pub fn on<F, Fut, E>(mut self, status: YapayTransactionStatus, handler: F) -> Self
+    where
+        F: WebhookHandler<Fut, E>,
+    {
+        self.handlers.insert(status, boxed_handler(handler));
+        self
+    }
+
+    #[must_use]
+    pub fn on_waiting_payment<F, Fut, E>(self, handler: F) -> Self
+    where
+        F: WebhookHandler<Fut, E>,
+    {
+        self.on(YapayTransactionStatus::AguardandoPagamento, handler)
+    }
+
+    #[must_use]
+    pub fn on_approved<F, Fut, E>(self, handler: F) -> Self
+    where
+        F: WebhookHandler<Fut, E>,
+    {
+        self.on(YapayTransactionStatus::Aprovada, handler)
+    }
+
+    #[must_use]
+    pub fn on_cancelled<F, Fut, E>(self, handler: F) -> Self
+    where
+        F: WebhookHandler<Fut, E>,
+    {
+        self.on(YapayTransactionStatus::Cancelada, handler)
+    }
+
+    #[must_use]
+    pub fn on_rejected<F, Fut, E>(self, handler: F) -> Self
+    where
+        F: WebhookHandler<Fut, E>,
+    {
+        self.on(YapayTransactionStatus::Reprovada, handler)
+    }
+
+    #[must_use]
+    pub fn on_dispute<F, Fut, E>(self, handler: F) -> Self
+    where
+        F: WebhookHandler<Fut, E>,
+    {
+        self.on(YapayTransactionStatus::Contestacao, handler)
+    }
+
+    /// Chargebacks are told apart from disputes by [`YapayWebhook::status`].
+    #[must_use]
+    pub fn on_chargeback<F, Fut, E>(self, handler: F) -> Self
+    where
+        F: WebhookHandler<Fut, E>,
+    {
+        self.on(YapayTransactionStatus::Chargeback, handler)
+    }
+
+    /// Handler used for every status that has no handler of its own.
+    #[must_use]
+    pub fn fallback<F, Fut, E>(mut self, handler: F) -> Self
+    where
+        F: WebhookHandler<Fut, E>,
+    {
+        self.fallback = Some(boxed_handler(handler));
+        self
+    }
+
+    /// Runs the handler registered for the status of `webhook`.
+    pub async fn dispatch(&self, webhook: YapayWebhook) -> Result<(), DispatchError> {
+        let status = webhook.status();
+
+        let handler = match self.handlers.get(&status).or(self.fallback.as_ref()) {
+            Some(handler) => handler,
+            None => {
+                tracing::debug!("no handler registered for status {}", status);
+                return Ok(());
+            }
+        };
+
+        handler(webhook).await.map_err(|source| {
+            tracing::error!("webhook handler for status {} failed: {}", status, source);
+            DispatchError::Handler { status, source }
+        })
+    }
+
+    /// Same as [`WebhookDispatcher::dispatch`], but returns the http status you should answer
+    /// Yapay with.
+    pub async fn dispatch_status(&self, webhook: YapayWebhook) -> StatusCode {
+        match self.dispatch(webhook).await {
+            Ok(()) => StatusCode::OK,
+            Err(err) => err.status_code(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn approved_webhook() -> YapayWebhook {
+        let raw = include_str!("../../tests/assets/webhook_notification.txt");
+        YapayWebhook::try_from(Vec::from(raw)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn t_dispatch_by_status() {
+        let approved = Arc::new(AtomicUsize::new(0));
+        let counter = approved.clone();
+
+        let dispatcher = WebhookDispatcher::new()
+            .on_approved(move |_| {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Ok::<_, HandlerError>(())
+                }
+            })
+            .on_cancelled(|_| async { Err::<(), _>("should not be called") });
+
+        let status = dispatcher.dispatch_status(approved_webhook()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(approved.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn t_dispatch_fallback() {
+        let dispatcher = WebhookDispatcher::new()
+            .on_cancelled(|_| async { Ok::<_, HandlerError>(()) })
+            .fallback(|_| async { Err::<(), _>("fallback failed") });
+
+        let res = dispatcher.dispatch(approved_webhook()).await;
+        assert!(matches!(
+            res,
+            Err(DispatchError::Handler {
+                status: YapayTransactionStatus::Aprovada,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn t_dispatch_chargeback() {
+        let dispatcher = WebhookDispatcher::new()
+            .on_dispute(|_| async { Err::<(), _>("not a dispute") })
+            .on_chargeback(|_| async { Ok::<_, HandlerError>(()) });
+
+        let mut webhook = approved_webhook();
+        webhook.transaction.status_id = YapayTransactionStatus::Contestacao;
+        webhook.transaction.status_name = "Chargeback".to_string();
+
+        assert!(dispatcher.dispatch(webhook).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn t_dispatch_without_handler() {
+        let dispatcher = WebhookDispatcher::new();
+
+        let status = dispatcher.dispatch_status(approved_webhook()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn t_dispatch_failure_is_not_ok() {
+        let dispatcher =
+            WebhookDispatcher::new().on_approved(|_| async { Err::<(), _>("database is down") });
+
+        let status = dispatcher.dispatch_status(approved_webhook()).await;
+        assert_ne!(status, StatusCode::OK);
+    }
+}
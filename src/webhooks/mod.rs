@@ -5,13 +5,40 @@
 //!
 //!
 //!
-//! `YapayWebhook` implements `From<Vec<u8>>`. You can use it on raw payloads sent to your
+//! `YapayWebhook` implements `TryFrom<Vec<u8>>`. You can use it on raw payloads sent to your
 //! `actix_web` server, for example.
+//!
+//! With the `axum` feature enabled, `YapayWebhook` can be used directly as an extractor, and
+//! [`ack_webhook_handler`] builds a handler that always acks Yapay with http 200.
+//!
+//! With the `actix` feature enabled, `YapayWebhook` implements actix's `FromRequest`, and its body
+//! size limit can be changed with [`WebhookExtractConfig`].
+//!
+//! [`WebhookDispatcher`] routes notifications to async handlers registered per
+//! [`YapayTransactionStatus`]. A failing handler becomes a non-200 response, so Yapay will try
+//! again later.
+//!
+//! [`WebhookDeduplicator`] drops notifications Yapay delivers twice or out of order, backed by any
+//! [`WebhookStore`].
+
+#[cfg(feature = "actix")]
+mod actix;
+#[cfg(feature = "axum")]
+mod axum;
+mod dedup;
+mod dispatcher;
 
 use serde::{Deserialize, Serialize};
 use serde_qs::Config;
 
+#[cfg(feature = "actix")]
+pub use self::actix::{WebhookExtractConfig, WebhookExtractError};
+#[cfg(feature = "axum")]
+pub use self::axum::{ack_webhook_handler, WebhookRejection};
+pub use self::dedup::{InMemoryWebhookStore, WebhookDeduplicator, WebhookStore, WebhookVerdict};
+pub use self::dispatcher::{WebhookDispatcher, WebhookHandler};
 use crate::common_types::{YapayTransactionStatus, CLEAN_WEBHOOK_REGEX};
+use crate::errors::WebhookError;
 
 /// The notification Yapay POSTs into your server when a transaction is created.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -20,15 +47,25 @@ pub struct YapayWebhook {
     pub transaction: WebhookTransaction,
 }
 
+impl YapayWebhook {
+    /// The transaction status, telling chargebacks apart from disputes, which share the same
+    /// `status_id`. See [`YapayTransactionStatus::with_name`].
+    pub fn status(&self) -> YapayTransactionStatus {
+        self.transaction
+            .status_id
+            .with_name(&self.transaction.status_name)
+    }
+}
+
 impl TryFrom<Vec<u8>> for YapayWebhook {
-    type Error = ();
+    type Error = WebhookError;
 
     fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
-        let value_as_string = String::from_utf8(value).map_err(|_| ())?;
+        let value_as_string = String::from_utf8(value).map_err(|_| WebhookError::Encoding)?;
 
         let cfg = Config::new(10, false);
         cfg.deserialize_bytes::<Self>(clean_non_indexed(&*value_as_string).as_ref())
-            .map_err(|_| ())
+            .map_err(WebhookError::from)
     }
 }
 
@@ -129,6 +166,11 @@ pub fn clean_non_indexed(raw: &str) -> String {
         locs.push((start, finish));
     }
 
+    // nothing to clean up, e.g. a payload without products
+    if locs.is_empty() {
+        return raw.to_string();
+    }
+
     let first_idx = locs[0].0 + 1;
     let last_idx = locs.last().map(|&(_, l)| l).unwrap();
 
@@ -15,7 +15,7 @@ async fn cc_payment() {
     let card_data = YapayCardData::new(
         PaymentCreditCard::MasterCard,
         "Joaquim Silva".to_string(),
-        "3456 8564 1548 7894".to_string(),
+        "5555 6666 7777 8884".to_string(),
         "06".to_string(),
         "2022".to_string(),
         "411".to_string(),
@@ -27,7 +27,7 @@ async fn cc_payment() {
 
     let transaction = YapayTransaction::online_goods(
         Uuid::new_v4().to_string()[..20].to_string(),
-        "127.0.0.1".to_string(),
+        "127.0.0.1".parse().unwrap(),
         Some(PaymentCreditCard::payment_methods_all()),
         Some("https://webhook.site/966172d0-ed8f-497f-afcf-eec1727a628b"),
     )
@@ -56,6 +56,23 @@ async fn t_simulate_payment_conditions() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn t_simulate_payment_restricted() -> anyhow::Result<()> {
+    let yapay_sdk = common::setup_sdk();
+
+    let res = yapay_sdk
+        .simulate_payment_for(100_f64, &[PaymentCreditCard::Visa], 6)?
+        .execute(YapayEnv::SANDBOX)
+        .await?;
+    assert!(res
+        .data_response
+        .payment_methods
+        .iter()
+        .all(|plan| plan.split <= 6));
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn t_checkout() -> anyhow::Result<()> {
     let yapay_sdk = common::setup_sdk();